@@ -0,0 +1,422 @@
+// 1. Turns content/<category>/<section>.md into the section registry
+// 2. Compiles the C half of the FFI section with the system C compiler
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    generate_sections(Path::new("content"), &out_dir.join("sections.rs"));
+
+    // Only the `ffi` feature needs the C library
+    if env::var_os("CARGO_FEATURE_FFI").is_some() {
+        compile_ffi(&out_dir);
+    }
+}
+
+/* ---------------------------------------------------------------------- */
+/* ------------------------    Content files    ------------------------- */
+/* ---------------------------------------------------------------------- */
+//
+// content/_index.md               categories: [basics, collections, ...]
+// content/<category>/_category.md title, feature, sections: [...], prose
+// content/<category>/<section>.md front matter, prose and fenced code
+//
+// Front matter sits between two `---` lines:
+//
+//     title: Tuple
+//     tags: [basics, tuple]
+//     see-also: [array-slice]
+//     expected-output: |
+//       indented lines, printed by the section
+//     no-output: true          instead of expected-output, for silent sections
+//     allow: [unused_labels]   lints the example trips on purpose, allowed on
+//                              its functions only
+//
+// ```rust blocks become the body of the section's function, ```rust,module
+// blocks are placed at module level (shared types, tests), ```rust,annotated
+// blocks are the body of a second function run by `cheatsheet annotate`,
+// ```rust,desugared blocks the body of a third one, the same example without
+// syntax sugar, run by `cheatsheet desugar`.
+// Other fences are only shown.
+
+struct FrontMatter {
+    path: PathBuf,
+    fields: Vec<(String, Value)>,
+}
+
+enum Value {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl FrontMatter {
+    fn text(&self, key: &str) -> Option<&str> {
+        match self.fields.iter().find(|(k, _)| k == key) {
+            Some((_, Value::Text(text))) => Some(text),
+            Some((_, Value::List(_))) => fail(&self.path, &format!("`{key}` must be a single value")),
+            None => None,
+        }
+    }
+
+    fn required(&self, key: &str) -> &str {
+        self.text(key)
+            .unwrap_or_else(|| fail(&self.path, &format!("missing `{key}`")))
+    }
+
+    fn list(&self, key: &str) -> Vec<String> {
+        match self.fields.iter().find(|(k, _)| k == key) {
+            Some((_, Value::List(list))) => list.clone(),
+            Some((_, Value::Text(_))) => fail(&self.path, &format!("`{key}` must be a [list]")),
+            None => Vec::new(),
+        }
+    }
+}
+
+struct Document {
+    front: FrontMatter,
+    // Everything after the front matter
+    body: String,
+    // ```rust blocks
+    code: String,
+    // ```rust,module blocks
+    module_code: String,
+    // ```rust,annotated blocks
+    annotated_code: String,
+    // ```rust,desugared blocks
+    desugared_code: String,
+}
+
+struct CategoryFile {
+    id: String,
+    title: String,
+    feature: Option<String>,
+    doc: Document,
+    sections: Vec<SectionFile>,
+}
+
+struct SectionFile {
+    id: String,
+    doc: Document,
+}
+
+fn fail(path: &Path, msg: &str) -> ! {
+    panic!("{}: {msg}", path.display())
+}
+
+fn read(path: &Path) -> String {
+    println!("cargo:rerun-if-changed={}", path.display());
+    fs::read_to_string(path).unwrap_or_else(|e| fail(path, &e.to_string()))
+}
+
+fn parse_document(path: &Path) -> Document {
+    let text = read(path);
+    let mut lines = text.lines().peekable();
+    if lines.next() != Some("---") {
+        fail(path, "expected front matter starting with `---`");
+    }
+
+    let mut fields = Vec::new();
+    loop {
+        let line = lines.next().unwrap_or_else(|| fail(path, "unterminated front matter"));
+        if line == "---" {
+            break;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .unwrap_or_else(|| fail(path, &format!("expected `key: value`, got `{line}`")));
+        let value = value.trim();
+        let value = if value == "|" {
+            // Block of lines indented by two spaces, blank lines included
+            let mut block = Vec::new();
+            while let Some(next) = lines.peek() {
+                if next.is_empty() || next.starts_with("  ") {
+                    block.push(next.get(2..).unwrap_or(""));
+                    lines.next();
+                } else {
+                    break;
+                }
+            }
+            while block.last() == Some(&"") {
+                block.pop();
+            }
+            Value::Text(block.iter().map(|l| format!("{l}\n")).collect())
+        } else if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Value::List(
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(String::from)
+                    .collect(),
+            )
+        } else {
+            Value::Text(unquote(value).to_owned())
+        };
+        fields.push((key.trim().to_owned(), value));
+    }
+
+    let body: Vec<&str> = lines.collect();
+    let mut code = String::new();
+    let mut module_code = String::new();
+    let mut annotated_code = String::new();
+    let mut desugared_code = String::new();
+    let mut fence: Option<&str> = None;
+    for line in &body {
+        match fence {
+            None => {
+                if let Some(info) = line.strip_prefix("```") {
+                    fence = Some(info.trim());
+                }
+            }
+            Some(_) if line.trim_end() == "```" => fence = None,
+            Some("rust") => writeln!(code, "{line}").unwrap(),
+            Some("rust,module") => writeln!(module_code, "{line}").unwrap(),
+            Some("rust,annotated") => writeln!(annotated_code, "{line}").unwrap(),
+            Some("rust,desugared") => writeln!(desugared_code, "{line}").unwrap(),
+            Some(_) => {}
+        }
+    }
+    if fence.is_some() {
+        fail(path, "unterminated code fence");
+    }
+
+    Document {
+        front: FrontMatter { path: path.to_owned(), fields },
+        body: body.join("\n").trim().to_owned(),
+        code,
+        module_code,
+        annotated_code,
+        desugared_code,
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn load_categories(content: &Path) -> Vec<CategoryFile> {
+    println!("cargo:rerun-if-changed={}", content.display());
+    let index = parse_document(&content.join("_index.md"));
+
+    let mut categories = Vec::new();
+    for id in index.front.list("categories") {
+        let dir = content.join(&id);
+        println!("cargo:rerun-if-changed={}", dir.display());
+        let doc = parse_document(&dir.join("_category.md"));
+        let title = doc.front.required("title").to_owned();
+        let feature = doc.front.text("feature").map(String::from);
+
+        let listed = doc.front.list("sections");
+        let mut sections = Vec::new();
+        for section_id in &listed {
+            let doc = parse_document(&dir.join(format!("{section_id}.md")));
+            doc.front.required("title");
+            sections.push(SectionFile { id: section_id.clone(), doc });
+        }
+
+        // Every section file must be listed, or it would silently disappear
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            let stem = path.file_stem().unwrap().to_string_lossy().into_owned();
+            if path.extension().is_some_and(|e| e == "md") && stem != "_category" && !listed.contains(&stem) {
+                fail(&path, "not listed in `sections` of _category.md");
+            }
+        }
+
+        categories.push(CategoryFile { id, title, feature, doc, sections });
+    }
+    categories
+}
+
+// Ids are looked up across categories, and name the generated functions
+fn check_ids(categories: &[CategoryFile]) {
+    let mut ids = HashSet::new();
+    for category in categories {
+        if !ids.insert(category.id.as_str()) {
+            fail(&category.doc.front.path, &format!("duplicate id `{}`", category.id));
+        }
+        for section in &category.sections {
+            if !ids.insert(section.id.as_str()) {
+                fail(&section.doc.front.path, &format!("duplicate id `{}`", section.id));
+            }
+        }
+    }
+    for section in categories.iter().flat_map(|c| &c.sections) {
+        for id in section.doc.front.list("see-also") {
+            if !ids.contains(id.as_str()) {
+                fail(&section.doc.front.path, &format!("see-also links to unknown id `{id}`"));
+            }
+        }
+    }
+}
+
+/* ---------------------------------------------------------------------- */
+/* ------------------------    Code generation    ----------------------- */
+/* ---------------------------------------------------------------------- */
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "type", "unsafe", "use", "where",
+    "while",
+];
+
+// `array-slice` -> `array_slice`, `match` -> `match_`
+fn ident(id: &str) -> String {
+    let ident = id.replace('-', "_");
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident + "_"
+    } else {
+        ident
+    }
+}
+
+fn str_list(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|i| format!("{i:?}")).collect();
+    format!("&[{}]", items.join(", "))
+}
+
+fn generate_sections(content: &Path, dest: &Path) {
+    let categories = load_categories(content);
+    check_ids(&categories);
+    let mut out = String::from("// Generated by build.rs from content/, do not edit\n");
+
+    for category in &categories {
+        let cfg = match &category.feature {
+            Some(feature) => format!("#[cfg(feature = {feature:?})]\n"),
+            None => String::new(),
+        };
+
+        write!(out, "\n{cfg}pub(crate) mod {} {{\n", ident(&category.id)).unwrap();
+        out.push_str("#[allow(unused_imports)]\nuse crate::out::{print, println};\n");
+
+        writeln!(out, "\npub(crate) const CATEGORY: crate::section::Category = crate::section::Category {{").unwrap();
+        writeln!(out, "    id: {:?},", category.id).unwrap();
+        writeln!(out, "    title: {:?},", category.title).unwrap();
+        writeln!(out, "    markdown: {:?},", category.doc.body).unwrap();
+        writeln!(out, "    module_code: {:?},", category.doc.module_code).unwrap();
+        writeln!(out, "    sections: &[").unwrap();
+        for section in &category.sections {
+            let front = &section.doc.front;
+            writeln!(out, "        crate::section::Section {{").unwrap();
+            writeln!(out, "            id: {:?},", section.id).unwrap();
+            writeln!(out, "            title: {:?},", front.required("title")).unwrap();
+            writeln!(out, "            tags: {},", str_list(&front.list("tags"))).unwrap();
+            writeln!(out, "            see_also: {},", str_list(&front.list("see-also"))).unwrap();
+            writeln!(out, "            expected_output: {:?},", front.text("expected-output")).unwrap();
+            writeln!(out, "            no_output: {},", front.text("no-output") == Some("true")).unwrap();
+            writeln!(out, "            markdown: {:?},", section.doc.body).unwrap();
+            writeln!(out, "            code: {:?},", section.doc.code).unwrap();
+            writeln!(out, "            module_code: {:?},", section.doc.module_code).unwrap();
+            writeln!(out, "            run: {},", ident(&section.id)).unwrap();
+            match section.doc.annotated_code.is_empty() {
+                true => writeln!(out, "            annotated: None,").unwrap(),
+                false => writeln!(out, "            annotated: Some({}_annotated),", section.id.replace('-', "_")).unwrap(),
+            }
+            writeln!(out, "            desugared_code: {:?},", section.doc.desugared_code).unwrap();
+            match section.doc.desugared_code.is_empty() {
+                true => writeln!(out, "            desugared: None,").unwrap(),
+                false => writeln!(out, "            desugared: Some({}_desugared),", section.id.replace('-', "_")).unwrap(),
+            }
+            writeln!(out, "        }},").unwrap();
+        }
+        out.push_str("    ],\n};\n");
+
+        for section in &category.sections {
+            let allow = section.doc.front.list("allow");
+            let allow = |extra: &[&str]| {
+                let lints: Vec<&str> = allow.iter().map(String::as_str).chain(extra.iter().copied()).collect();
+                match lints.is_empty() {
+                    true => String::new(),
+                    false => format!("#[allow({})]\n", lints.join(", ")),
+                }
+            };
+            write!(out, "\n{}fn {}() {{\n{}}}\n", allow(&[]), ident(&section.id), section.doc.code).unwrap();
+            if !section.doc.annotated_code.is_empty() {
+                let code = &section.doc.annotated_code;
+                write!(out, "\n{}fn {}_annotated() {{\n{code}}}\n", allow(&[]), section.id.replace('-', "_")).unwrap();
+            }
+            if !section.doc.desugared_code.is_empty() {
+                let code = &section.doc.desugared_code;
+                // What the compiler expands to is what these lints flag
+                let lints = allow(&["clippy::while_let_loop", "clippy::useless_conversion"]);
+                write!(out, "\n{lints}fn {}_desugared() {{\n{code}}}\n", section.id.replace('-', "_")).unwrap();
+            }
+        }
+
+        // Module level code last, it may end with a test module
+        out.push('\n');
+        out.push_str(&category.doc.module_code);
+        for section in &category.sections {
+            out.push_str(&section.doc.module_code);
+        }
+        out.push_str("}\n");
+    }
+
+    out.push_str("\npub(crate) const CATEGORIES: &[crate::section::Category] = &[\n");
+    for category in &categories {
+        if let Some(feature) = &category.feature {
+            writeln!(out, "    #[cfg(feature = {feature:?})]").unwrap();
+        }
+        writeln!(out, "    {}::CATEGORY,", ident(&category.id)).unwrap();
+    }
+    out.push_str("];\n");
+
+    // Categories left out of this build by cargo features
+    out.push_str("\npub(crate) const DISABLED: &[crate::section::DisabledCategory] = &[\n");
+    for category in &categories {
+        if let Some(feature) = &category.feature {
+            writeln!(out, "    #[cfg(not(feature = {feature:?}))]").unwrap();
+            writeln!(
+                out,
+                "    crate::section::DisabledCategory {{ id: {:?}, title: {:?}, feature: {:?} }},",
+                category.id, category.title, feature
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("];\n");
+
+    fs::write(dest, out).unwrap();
+}
+
+/* ---------------------------------------------------------------------- */
+/* ------------------------------    FFI    ----------------------------- */
+/* ---------------------------------------------------------------------- */
+
+fn compile_ffi(out_dir: &Path) {
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+    let ar = env::var("AR").unwrap_or_else(|_| "ar".to_owned());
+
+    let src = "c/ffi_demo.c";
+    let obj = out_dir.join("ffi_demo.o");
+    let lib = out_dir.join("libffi_demo.a");
+
+    let status = Command::new(&cc)
+        .args(["-c", "-fPIC", "-O2", src, "-o"])
+        .arg(&obj)
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run `{cc}`: {e}"));
+    assert!(status.success(), "`{cc}` failed to compile {src}");
+
+    let status = Command::new(&ar)
+        .arg("crs")
+        .arg(&lib)
+        .arg(&obj)
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run `{ar}`: {e}"));
+    assert!(status.success(), "`{ar}` failed to archive {}", obj.display());
+
+    println!("cargo:rustc-link-search=native={}", out_dir.display());
+    println!("cargo:rustc-link-lib=static=ffi_demo");
+    println!("cargo:rerun-if-changed={src}");
+    println!("cargo:rerun-if-env-changed=CC");
+    println!("cargo:rerun-if-env-changed=AR");
+}
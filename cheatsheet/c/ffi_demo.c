@@ -0,0 +1,38 @@
+/* C half of the FFI section. Compiled into a static library by build.rs */
+#include <ctype.h>
+#include <stddef.h>
+
+/* Implemented in Rust with #[no_mangle] extern "C" */
+extern int rust_square(int x);
+
+int c_add(int a, int b) {
+    return a + b;
+}
+
+/* Calls back into Rust through the exported symbol */
+int c_sum_of_squares(int a, int b) {
+    return rust_square(a) + rust_square(b);
+}
+
+/* Calls back into Rust through a function pointer */
+int c_apply(int (*f)(int), int x) {
+    return f(x);
+}
+
+/* Static string owned by C, Rust must not free it */
+const char *c_greeting(void) {
+    return "Hello from C!";
+}
+
+/* Writes an upper-cased copy of `in` into `out`, returns the length written */
+size_t c_to_upper(const char *in, char *out, size_t cap) {
+    size_t i = 0;
+    if (cap == 0) {
+        return 0;
+    }
+    for (; in[i] != '\0' && i + 1 < cap; i++) {
+        out[i] = (char)toupper((unsigned char)in[i]);
+    }
+    out[i] = '\0';
+    return i;
+}
@@ -0,0 +1,284 @@
+//! The code rustc generates for some sections, shown side by side, run by
+//! `cheatsheet asm <topic>`.
+//!
+//! Each topic is a small library with two `#[no_mangle]` functions doing
+//! the same thing two ways, compiled with `-C opt-level=3`. Identical (or
+//! nearly) output on both sides is what "zero-cost abstraction" means.
+use crate::Cheatsheet;
+use std::fmt::Write;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{env, fs, process};
+
+/// What to ask rustc for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// `--emit asm`, after optimizations.
+    Asm,
+    /// `-Zunpretty=mir`, before LLVM. Needs a nightly rustc, or
+    /// `RUSTC_BOOTSTRAP=1` in the environment.
+    Mir,
+}
+
+/// Two ways of writing the same function.
+pub struct Topic {
+    /// The section the code comes from.
+    pub id: &'static str,
+    pub title: &'static str,
+    pub left: &'static str,
+    pub right: &'static str,
+    source: fn() -> String,
+}
+
+impl Topic {
+    /// The library that is compiled.
+    pub fn source(&self) -> String {
+        (self.source)()
+    }
+}
+
+pub fn topics() -> &'static [Topic] {
+    TOPICS
+}
+
+pub fn topic(id: &str) -> Option<&'static Topic> {
+    TOPICS.iter().find(|t| t.id == id)
+}
+
+/// Compiles `topic` and returns the two functions next to each other, or
+/// what the compiler said when it failed.
+pub fn show(topic: &Topic, emit: Emit) -> Result<String, String> {
+    let text = compile(&topic.source(), emit)?;
+    let left = function(&text, topic.left, emit).ok_or_else(|| format!("`{}` not found in the output", topic.left))?;
+    let right = function(&text, topic.right, emit).ok_or_else(|| format!("`{}` not found in the output", topic.right))?;
+    Ok(side_by_side((topic.left, &left), (topic.right, &right)))
+}
+
+/// Runs `$RUSTC` (or `rustc`) on `source` as an optimized library.
+pub fn compile(source: &str, emit: Emit) -> Result<String, String> {
+    // One directory per call, tests compile in parallel
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let call = CALLS.fetch_add(1, Ordering::Relaxed);
+    let dir = env::temp_dir().join(format!("cheatsheet-asm-{}-{call}", process::id()));
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let file = dir.join("topic.rs");
+    let out = dir.join("topic.out");
+    fs::write(&file, source).map_err(|e| e.to_string())?;
+
+    let compiler = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let mut command = Command::new(&compiler);
+    command.args(["--edition", "2021", "--crate-type", "lib", "--crate-name", "topic", "-C", "opt-level=3"]);
+    match emit {
+        Emit::Asm => command.args(["--emit", "asm", "-C", "debuginfo=0"]),
+        Emit::Mir => command.args(["-Z", "unpretty=mir"]),
+    };
+    let output = command.arg("-o").arg(&out).arg(&file).output();
+    let text = fs::read_to_string(&out);
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let output = output.map_err(|e| format!("{compiler}: {e}"))?;
+    if !output.status.success() {
+        let mut error = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+        if emit == Emit::Mir {
+            error.push_str("\nhint: MIR needs a nightly rustc, or RUSTC_BOOTSTRAP=1 in the environment");
+        }
+        return Err(error);
+    }
+    text.map_err(|e| e.to_string())
+}
+
+/// The lines of function `name` in rustc's output, without the assembler
+/// directives and comments.
+pub fn function(text: &str, name: &str, emit: Emit) -> Option<Vec<String>> {
+    let mut lines = text.lines();
+    let body = match emit {
+        Emit::Asm => {
+            // LLVM merges functions that compile to the same code, one of
+            // them is then only an alias
+            let alias = text.lines().find_map(|line| {
+                let line = line.trim().trim_start_matches(".set").trim_start();
+                let (from, to) = line.split_once(" = ").or_else(|| line.split_once(", "))?;
+                (from.trim_start_matches('_') == name).then(|| to.trim().trim_start_matches('_'))
+            });
+            if let Some(other) = alias {
+                let mut body = vec![format!("{name} = {other}, same code")];
+                body.extend(function(text, other, emit)?);
+                return Some(body);
+            }
+            // `_name` on macOS
+            let label = |line: &str| line == format!("{name}:") || line == format!("_{name}:");
+            lines.find(|line| label(line))?;
+            lines
+                .take_while(|line| !line.starts_with(".Lfunc_end") && !line.starts_with("Lfunc_end"))
+                .map(|line| line.split('#').next().unwrap().trim_end())
+                .filter(|line| {
+                    let code = line.trim_start();
+                    // Jump targets are kept, other local labels only mark unwinding ranges
+                    !code.is_empty() && (!code.starts_with('.') || code.starts_with(".LBB"))
+                })
+                .map(|line| line.replace('\t', " ").trim_start().to_owned())
+                .collect()
+        }
+        Emit::Mir => {
+            let start = format!("fn {name}(");
+            let first = lines.find(|line| line.starts_with(&start))?;
+            let mut body = vec![first.to_owned()];
+            body.extend(lines.by_ref().take_while(|line| *line != "}").map(String::from));
+            body.push("}".to_owned());
+            body
+        }
+    };
+    Some(body)
+}
+
+// Two columns, long lines are cut so the right one stays aligned
+fn side_by_side(left: (&str, &[String]), right: (&str, &[String])) -> String {
+    const WIDTH: usize = 48;
+    let cut = |line: &str| match line.char_indices().nth(WIDTH - 1) {
+        Some((end, _)) => format!("{}~", &line[..end]),
+        None => line.to_owned(),
+    };
+
+    let mut out = String::new();
+    writeln!(out, "{:<WIDTH$} | {}", left.0, right.0).unwrap();
+    writeln!(out, "{}-+-{}", "-".repeat(WIDTH), "-".repeat(WIDTH)).unwrap();
+    for i in 0..left.1.len().max(right.1.len()) {
+        let l = left.1.get(i).map_or(String::new(), |line| cut(line));
+        let r = right.1.get(i).map_or(String::new(), |line| cut(line));
+        writeln!(out, "{:<WIDTH$} | {}", l, r.trim_end()).unwrap();
+    }
+    writeln!(out, "\n{} lines | {} lines", left.1.len(), right.1.len()).unwrap();
+    out
+}
+
+/* ---------------------------------------------------------------------- */
+/* ---------------------------    Topics    ----------------------------- */
+/* ---------------------------------------------------------------------- */
+
+const TOPICS: &[Topic] = &[
+    Topic {
+        id: "iterator-usage",
+        title: "map().collect() vs a for loop pushing into a Vec",
+        left: "incremented_map",
+        right: "incremented_loop",
+        source: iterator_source,
+    },
+    Topic {
+        id: "operator-overloading",
+        title: "a + b through impl Add for Point vs adding the fields by hand",
+        left: "add_points",
+        right: "add_fields",
+        source: operator_source,
+    },
+];
+
+// The section's v2 and v3, each in a function taking v1
+fn iterator_source() -> String {
+    let section = Cheatsheet::section("iterator-usage").expect("iterator-usage section");
+    let code = section.code();
+    let map = code.lines().find(|line| line.starts_with("let v2")).expect("`let v2` in iterator-usage");
+    let start = code.find("let mut v3").expect("`let mut v3` in iterator-usage");
+    let end = start + code[start..].find("\n}\n").expect("end of the for loop") + 2;
+    let indent = |code: &str| code.lines().map(|line| format!("    {line}\n")).collect::<String>();
+    format!(
+        "\
+#[no_mangle]
+pub fn incremented_map(v1: &[i32]) -> Vec<i32> {{
+{}    v2
+}}
+
+#[no_mangle]
+pub fn incremented_loop(v1: &[i32]) -> Vec<i32> {{
+{}    v3
+}}
+",
+        indent(map),
+        indent(&code[start..end])
+    )
+}
+
+// The section's own Point and impl Add
+fn operator_source() -> String {
+    let section = Cheatsheet::section("operator-overloading").expect("operator-overloading section");
+    format!(
+        "{}
+#[no_mangle]
+pub fn add_points(a: Point, b: Point) -> Point {{
+    a + b
+}}
+
+#[no_mangle]
+pub fn add_fields(a: Point, b: Point) -> Point {{
+    Point {{ x: a.x + b.x, y: a.y + b.y }}
+}}
+",
+        section.code()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASM: &str = "\
+\t.section\t.text.twice,\"ax\",@progbits
+\t.globl\ttwice
+\t.p2align\t4
+\t.type\ttwice,@function
+twice:
+\t.cfi_startproc
+\tleal\t(%rdi,%rdi), %eax
+\tretq
+.Lfunc_end0:
+\t.size\ttwice, .Lfunc_end0-twice
+\t.cfi_endproc
+";
+
+    #[test]
+    fn topics_name_sections() {
+        for topic in topics() {
+            assert!(Cheatsheet::section(topic.id).is_some(), "`{}` is not a section id", topic.id);
+        }
+    }
+
+    #[test]
+    fn asm_keeps_instructions_only() {
+        assert_eq!(function(ASM, "twice", Emit::Asm).unwrap(), ["leal (%rdi,%rdi), %eax", "retq"]);
+        assert_eq!(function(ASM, "thrice", Emit::Asm), None);
+    }
+
+    #[test]
+    fn mir_keeps_the_whole_body() {
+        let mir = "fn other() -> () {\n}\n\nfn twice(_1: i32) -> i32 {\n    bb0: {\n        return;\n    }\n}\n";
+        let body = function(mir, "twice", Emit::Mir).unwrap();
+        assert_eq!(body.first().unwrap(), "fn twice(_1: i32) -> i32 {");
+        assert_eq!(body.len(), 5);
+    }
+
+    #[test]
+    fn sources_come_from_the_sections() {
+        let source = topic("iterator-usage").unwrap().source();
+        assert!(source.contains("    let v2: Vec<_> = v1.iter().map(|x| x + 1).collect();\n    v2\n}"));
+        assert!(source.contains("        v3.push(x + 1);\n    }\n    v3\n}"));
+        assert!(topic("operator-overloading").unwrap().source().contains("impl Add for Point"));
+    }
+
+    // cargo test impl_add -- --ignored
+    #[test]
+    #[ignore = "runs rustc, whether the two are merged depends on the LLVM version"]
+    fn impl_add_compiles_to_the_same_code() {
+        let topic = topic("operator-overloading").unwrap();
+        let text = compile(&topic.source(), Emit::Asm).unwrap();
+        // LLVM may merge the two, one is then an alias of the other
+        let body = |name| {
+            let body = function(&text, name, Emit::Asm).unwrap();
+            match body.first() {
+                Some(first) if first.ends_with(", same code") => body[1..].to_vec(),
+                _ => body,
+            }
+        };
+        assert!(!body("add_fields").is_empty());
+        assert_eq!(body("add_points"), body("add_fields"));
+        assert!(show(topic, Emit::Asm).unwrap().starts_with("add_points "));
+    }
+}
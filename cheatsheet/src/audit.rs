@@ -0,0 +1,232 @@
+//! Builds every section on its own with the local toolchain and collects
+//! the compiler's diagnostics, run by `cheatsheet audit`.
+//!
+//! Inside the library the examples are compiled with the lints of their
+//! `allow:` front matter allowed, this shows what rustc (and clippy, when
+//! installed) would say about each of them as a standalone program.
+use crate::json::Json;
+use crate::lint::Level;
+use crate::{Cheatsheet, Section};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::{env, fmt, fs, io, process, thread};
+
+/// The compiler used to build the sections.
+#[derive(Debug)]
+pub struct Toolchain {
+    compiler: String,
+    clippy: bool,
+    // The cheatsheet library, for the sections that call into `crate::`
+    library: Option<PathBuf>,
+}
+
+impl Toolchain {
+    /// `clippy-driver` when it is installed, since it reports rustc's own
+    /// lints as well, otherwise `$RUSTC` or `rustc`.
+    pub fn detect() -> Toolchain {
+        let clippy = Command::new("clippy-driver")
+            .arg("-V")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        let compiler = match clippy {
+            true => "clippy-driver".to_owned(),
+            false => env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned()),
+        };
+        Toolchain { compiler, clippy, library: find_library() }
+    }
+
+    pub fn compiler(&self) -> &str {
+        &self.compiler
+    }
+
+    pub fn has_clippy(&self) -> bool {
+        self.clippy
+    }
+}
+
+// cargo puts libcheatsheet.rlib next to the binary, tests run from deps/
+fn find_library() -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    exe.ancestors()
+        .skip(1)
+        .take(2)
+        .map(|dir| dir.join("libcheatsheet.rlib"))
+        .find(|lib| lib.is_file())
+}
+
+/// One diagnostic of the compiler about a section.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub level: Level,
+    /// The lint name, e.g. `dead_code` or `clippy::len_zero`.
+    pub lint: Option<String>,
+    pub message: String,
+    /// The line of the section the diagnostic points at.
+    pub source_line: Option<String>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.level {
+            Level::Warning => "warning",
+            Level::Error => "error",
+        };
+        match &self.lint {
+            Some(lint) => write!(f, "{level}[{lint}]: {}", self.message)?,
+            None => write!(f, "{level}: {}", self.message)?,
+        }
+        if let Some(line) = &self.source_line {
+            write!(f, "\n    | {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The section as a standalone program: the module level code of its
+/// category, then its own code as `main`. Returns the source and the range
+/// of lines (1-based, inclusive) that belong to the section.
+pub fn standalone_source(section: &Section) -> (String, (usize, usize)) {
+    let category = Cheatsheet::categories()
+        .iter()
+        .find(|c| c.sections().iter().any(|s| s.id() == section.id()))
+        .expect("section belongs to a category");
+
+    let mut source = format!("// `{}`, built by `cheatsheet audit`\n", section.id());
+    source.push_str(category.module_code());
+    for other in category.sections().iter().filter(|s| s.id() != section.id()) {
+        source.push_str(other.module_code());
+    }
+    let first = source.lines().count() + 1;
+    source.push_str(section.module_code());
+    source.push_str("fn main() {\n");
+    source.push_str(section.code());
+    source.push_str("}\n");
+    let last = source.lines().count();
+
+    // The library is linked in as an external crate
+    (source.replace("crate::", "cheatsheet::"), (first, last))
+}
+
+/// Builds one section in `dir` and returns the diagnostics that point into
+/// it. Errors anywhere are kept, they mean the section does not build.
+pub fn audit(section: &Section, toolchain: &Toolchain, dir: &Path) -> io::Result<Vec<Finding>> {
+    let (source, (first, last)) = standalone_source(section);
+    let file = dir.join(format!("{}.rs", section.id()));
+    fs::write(&file, &source)?;
+
+    let mut command = Command::new(&toolchain.compiler);
+    command
+        .args(["--edition", "2021", "--crate-type", "bin", "--crate-name", "section"])
+        .args(["--emit", "metadata", "--error-format", "json", "-o"])
+        .arg(dir.join(format!("{}.rmeta", section.id())))
+        .arg(&file);
+    if let Some(library) = &toolchain.library {
+        command.arg("--extern").arg(format!("cheatsheet={}", library.display()));
+    }
+    let output = command.output()?;
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut findings = Vec::new();
+    for diagnostic in String::from_utf8_lossy(&output.stderr).lines().filter_map(Json::parse) {
+        let level = match diagnostic.get("level").as_str() {
+            Some("error") => Level::Error,
+            Some("warning") => Level::Warning,
+            _ => continue,
+        };
+        let spans = diagnostic.get("spans").as_array();
+        // "aborting due to ..." and "N warnings emitted" have no span
+        if spans.is_empty() && diagnostic.get("code") == &Json::Null {
+            continue;
+        }
+        let line = spans
+            .iter()
+            .find(|span| span.get("is_primary").as_bool() == Some(true))
+            .and_then(|span| span.get("line_start").as_u64())
+            .map(|line| line as usize);
+        let in_section = line.is_some_and(|line| (first..=last).contains(&line));
+        if level == Level::Warning && !in_section {
+            continue;
+        }
+        findings.push(Finding {
+            level,
+            lint: diagnostic.get("code").get("code").as_str().map(String::from),
+            message: diagnostic.get("message").as_str().unwrap_or_default().to_owned(),
+            source_line: line.and_then(|line| lines.get(line - 1)).map(|text| text.trim().to_owned()),
+        });
+    }
+
+    if !output.status.success() && !findings.iter().any(|f| f.level == Level::Error) {
+        findings.push(Finding {
+            level: Level::Error,
+            lint: None,
+            message: format!("{} failed: {}", toolchain.compiler, String::from_utf8_lossy(&output.stderr).trim()),
+            source_line: None,
+        });
+    }
+    Ok(findings)
+}
+
+/// Audits `sections` on all cores, results are in the order of `sections`.
+pub fn audit_all(sections: &[&Section], toolchain: &Toolchain) -> io::Result<Vec<Vec<Finding>>> {
+    let dir = env::temp_dir().join(format!("cheatsheet-audit-{}", process::id()));
+    fs::create_dir_all(&dir)?;
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+    let workers = thread::available_parallelism().map_or(1, usize::from).min(sections.len());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(section) = sections.get(index) else { break };
+                let result = audit(section, toolchain, &dir);
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+    fs::remove_dir_all(&dir)?;
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standalone_source_wraps_code_in_main() {
+        let section = Cheatsheet::section("loop").unwrap();
+        let (source, (first, last)) = standalone_source(section);
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(lines[first - 1], "fn main() {");
+        assert_eq!(lines[first], "let mut count = 0;");
+        assert_eq!(lines[last - 1], "}");
+    }
+
+    #[test]
+    fn standalone_source_links_the_library() {
+        let section = Cheatsheet::section("trait-bounds").unwrap();
+        let (source, _) = standalone_source(section);
+        assert!(source.contains("use cheatsheet::largest;"));
+        assert!(!source.contains("crate::"));
+    }
+
+    #[test]
+    fn reports_warnings_of_the_section_only() {
+        let dir = env::temp_dir().join(format!("cheatsheet-audit-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let toolchain = Toolchain::detect();
+        let tuple = audit(Cheatsheet::section("tuple").unwrap(), &toolchain, &dir).unwrap();
+        let renaming = audit(Cheatsheet::section("renaming-with-as").unwrap(), &toolchain, &dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(tuple, []);
+        assert_eq!(renaming.len(), 2);
+        assert_eq!(renaming[0].lint.as_deref(), Some("unused_imports"));
+        assert_eq!(renaming[0].source_line.as_deref(), Some("use std::fmt::Result;"));
+    }
+}
@@ -0,0 +1,327 @@
+//! Times the alternatives some sections present side by side, run by
+//! `cheatsheet bench <topic>`.
+//!
+//! No benchmark crate is needed: each alternative is calibrated to run for
+//! a fixed time per sample, then sampled a few times to get the mean and
+//! the spread of ns/iter. Build with `--release`, debug timings say little.
+//!
+//! ```
+//! use cheatsheet::bench::{self, Config};
+//! use std::time::Duration;
+//!
+//! let config = Config { sizes: vec![10], samples: 3, sample_time: Duration::from_micros(100) };
+//! let results = bench::run(bench::topic("function-pointers").unwrap(), &config);
+//! assert_eq!(results.len(), 2); // fn pointer and generic, one size
+//! ```
+use crate::sections::generics::{button, select_box, text_field, Component, Draw, GenericScreen, Screen};
+use std::fmt::{self, Write};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// One way of doing the thing a topic compares.
+pub struct Alternative {
+    pub name: &'static str,
+    // Builds the input for a size outside of the timing, returns the
+    // routine that is timed. Its result is passed to `black_box`
+    setup: fn(usize) -> Box<dyn FnMut() -> u64>,
+}
+
+/// A set of alternatives shown by one or more sections.
+pub struct Topic {
+    /// The section or category the alternatives come from.
+    pub id: &'static str,
+    pub title: &'static str,
+    pub alternatives: &'static [Alternative],
+}
+
+impl fmt::Debug for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = self.alternatives.iter().map(|a| a.name).collect();
+        f.debug_struct("Topic").field("id", &self.id).field("alternatives", &names).finish()
+    }
+}
+
+/// How long and over which input sizes to run.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub sizes: Vec<usize>,
+    pub samples: usize,
+    pub sample_time: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { sizes: vec![10, 1_000, 100_000], samples: 10, sample_time: Duration::from_millis(20) }
+    }
+}
+
+/// The timing of one alternative at one input size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub alternative: &'static str,
+    pub size: usize,
+    /// Mean over the samples.
+    pub ns_per_iter: f64,
+    /// Standard deviation over the samples.
+    pub deviation: f64,
+}
+
+pub fn topics() -> &'static [Topic] {
+    TOPICS
+}
+
+pub fn topic(id: &str) -> Option<&'static Topic> {
+    TOPICS.iter().find(|t| t.id == id)
+}
+
+/// Times every alternative of `topic` at every size of `config`.
+pub fn run(topic: &Topic, config: &Config) -> Vec<Measurement> {
+    let mut results = Vec::new();
+    for &size in &config.sizes {
+        for alternative in topic.alternatives {
+            let mut routine = (alternative.setup)(size);
+            let iterations = calibrate(&mut routine, config.sample_time);
+            let samples: Vec<f64> = (0..config.samples.max(1))
+                .map(|_| time(&mut routine, iterations).as_nanos() as f64 / iterations as f64)
+                .collect();
+            let (ns_per_iter, deviation) = mean_and_deviation(&samples);
+            results.push(Measurement { alternative: alternative.name, size, ns_per_iter, deviation });
+        }
+    }
+    results
+}
+
+/// The results as a table, one row per alternative and size.
+pub fn table(topic: &Topic, results: &[Measurement]) -> String {
+    let mut table = format!("{}: {}\n\n", topic.id, topic.title);
+    let width = topic.alternatives.iter().map(|a| a.name.len()).max().unwrap_or(0).max("alternative".len());
+    writeln!(table, "{:<width$} {:>9} {:>14} {:>12}", "alternative", "size", "ns/iter", "+/-").unwrap();
+    for (i, m) in results.iter().enumerate() {
+        if i > 0 && results[i - 1].size != m.size {
+            table.push('\n');
+        }
+        writeln!(table, "{:<width$} {:>9} {:>14.1} {:>12.1}", m.alternative, m.size, m.ns_per_iter, m.deviation)
+            .unwrap();
+    }
+    table
+}
+
+fn time(routine: &mut dyn FnMut() -> u64, iterations: u64) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(routine());
+    }
+    start.elapsed()
+}
+
+// Iterations that take about `target`, doubling from 1 until the clock
+// measures something meaningful
+fn calibrate(routine: &mut dyn FnMut() -> u64, target: Duration) -> u64 {
+    let mut iterations = 1u64;
+    loop {
+        let elapsed = time(routine, iterations);
+        if elapsed >= target / 10 || iterations >= 1 << 32 {
+            let scaled = iterations as f64 * target.as_nanos() as f64 / elapsed.as_nanos().max(1) as f64;
+            return (scaled as u64).max(1);
+        }
+        iterations *= 2;
+    }
+}
+
+fn mean_and_deviation(samples: &[f64]) -> (f64, f64) {
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+    (mean, variance.sqrt())
+}
+
+/* ---------------------------------------------------------------------- */
+/* ---------------------------    Topics    ----------------------------- */
+/* ---------------------------------------------------------------------- */
+
+const TOPICS: &[Topic] = &[
+    Topic {
+        id: "iterating-errors",
+        title: "filter_map() vs collect::<Result<Vec<_>, _>>() vs partition()",
+        alternatives: &[
+            Alternative { name: "filter_map", setup: filter_map },
+            Alternative { name: "collect Result", setup: collect_result },
+            Alternative { name: "partition", setup: partition },
+        ],
+    },
+    Topic {
+        id: "returning-closures",
+        title: "impl Fn vs Box<dyn Fn>, calling the returned closure size times",
+        alternatives: &[
+            Alternative { name: "impl Fn", setup: impl_fn },
+            Alternative { name: "Box<dyn Fn>", setup: box_dyn_fn },
+        ],
+    },
+    Topic {
+        id: "function-pointers",
+        title: "fn pointer vs generic closure parameter in _do_twice, size calls",
+        alternatives: &[
+            Alternative { name: "fn pointer", setup: fn_pointer },
+            Alternative { name: "generic", setup: generic },
+        ],
+    },
+    Topic {
+        id: "trait-objects",
+        title: "Screen of Box<dyn Draw> vs a GenericScreen<T> per type vs GenericScreen<Component>, size of each",
+        alternatives: &[
+            Alternative { name: "dyn Draw", setup: dyn_draw },
+            Alternative { name: "generic", setup: generic_draw },
+            Alternative { name: "enum", setup: enum_draw },
+        ],
+    },
+];
+
+// Numbers as strings, the last one doesn't parse so collect() can't stop early
+fn strings(size: usize) -> Vec<String> {
+    if size == 0 {
+        return Vec::new();
+    }
+    let mut strings: Vec<String> = (0..size - 1).map(|n| n.to_string()).collect();
+    strings.push("LGR".to_owned());
+    strings
+}
+
+fn filter_map(size: usize) -> Box<dyn FnMut() -> u64> {
+    let strings = strings(size);
+    Box::new(move || {
+        let numbers: Vec<u64> = strings.iter().filter_map(|s| s.parse().ok()).collect();
+        numbers.len() as u64
+    })
+}
+
+fn collect_result(size: usize) -> Box<dyn FnMut() -> u64> {
+    let strings = strings(size);
+    Box::new(move || {
+        let numbers: Result<Vec<u64>, _> = strings.iter().map(|s| s.parse::<u64>()).collect();
+        numbers.map_or(0, |numbers| numbers.len() as u64)
+    })
+}
+
+fn partition(size: usize) -> Box<dyn FnMut() -> u64> {
+    let strings = strings(size);
+    Box::new(move || {
+        let (numbers, errors): (Vec<_>, Vec<_>) = strings.iter().map(|s| s.parse::<u64>()).partition(Result::is_ok);
+        let numbers: Vec<u64> = numbers.into_iter().map(Result::unwrap).collect();
+        (numbers.len() + errors.len()) as u64
+    })
+}
+
+fn add_one() -> impl Fn(u64) -> u64 {
+    |x| x + 1
+}
+
+fn add_or_subtract(x: u64) -> Box<dyn Fn(u64) -> u64> {
+    if x > 10 {
+        Box::new(move |y| y + x)
+    } else {
+        Box::new(move |y| y.wrapping_sub(x))
+    }
+}
+
+fn impl_fn(size: usize) -> Box<dyn FnMut() -> u64> {
+    let f = add_one();
+    Box::new(move || (0..size as u64).map(|n| f(black_box(n))).fold(0, u64::wrapping_add))
+}
+
+fn box_dyn_fn(size: usize) -> Box<dyn FnMut() -> u64> {
+    // Which closure is returned is only known at runtime
+    let f = add_or_subtract(black_box(11));
+    Box::new(move || (0..size as u64).map(|n| f(black_box(n))).fold(0, u64::wrapping_add))
+}
+
+fn double(x: u64) -> u64 {
+    x * 2
+}
+
+fn do_twice(f: fn(u64) -> u64, arg: u64) -> u64 {
+    f(arg) + f(arg)
+}
+
+fn do_twice_generic<F: Fn(u64) -> u64>(f: F, arg: u64) -> u64 {
+    f(arg) + f(arg)
+}
+
+fn fn_pointer(size: usize) -> Box<dyn FnMut() -> u64> {
+    Box::new(move || {
+        // A pointer the optimizer can't see through, as if chosen at runtime
+        let f: fn(u64) -> u64 = black_box(double);
+        (0..size as u64).map(|n| do_twice(f, black_box(n))).fold(0, u64::wrapping_add)
+    })
+}
+
+fn generic(size: usize) -> Box<dyn FnMut() -> u64> {
+    Box::new(move || (0..size as u64).map(|n| do_twice_generic(double, black_box(n))).fold(0, u64::wrapping_add))
+}
+
+// The trait-objects section's own screens, with size components of each
+// kind, grouped by kind so that the three draw the same text
+fn dyn_draw(size: usize) -> Box<dyn FnMut() -> u64> {
+    let mut components: Vec<Box<dyn Draw>> = Vec::new();
+    components.extend((0..size).map(|_| Box::new(button()) as Box<dyn Draw>));
+    components.extend((0..size).map(|_| Box::new(select_box()) as Box<dyn Draw>));
+    components.extend((0..size).map(|_| Box::new(text_field()) as Box<dyn Draw>));
+    let screen = Screen { components };
+    Box::new(move || screen.run().len() as u64)
+}
+
+fn generic_draw(size: usize) -> Box<dyn FnMut() -> u64> {
+    // One screen per type of component
+    let buttons = GenericScreen { components: (0..size).map(|_| button()).collect() };
+    let select_boxes = GenericScreen { components: (0..size).map(|_| select_box()).collect() };
+    let text_fields = GenericScreen { components: (0..size).map(|_| text_field()).collect() };
+    Box::new(move || (buttons.run().len() + select_boxes.run().len() + text_fields.run().len()) as u64)
+}
+
+fn enum_draw(size: usize) -> Box<dyn FnMut() -> u64> {
+    let mut components = Vec::new();
+    components.extend((0..size).map(|_| Component::Button(button())));
+    components.extend((0..size).map(|_| Component::SelectBox(select_box())));
+    components.extend((0..size).map(|_| Component::TextField(text_field())));
+    let screen = GenericScreen { components };
+    Box::new(move || screen.run().len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cheatsheet;
+
+    #[test]
+    fn topics_name_sections_or_categories() {
+        for topic in topics() {
+            let known = Cheatsheet::section(topic.id).is_some() || Cheatsheet::category(topic.id).is_some();
+            assert!(known, "`{}` is not a section or category id", topic.id);
+        }
+    }
+
+    #[test]
+    fn alternatives_compute_the_same_thing() {
+        let sum = |setup: fn(usize) -> Box<dyn FnMut() -> u64>| setup(100)();
+        assert_eq!(sum(filter_map), 99);
+        assert_eq!(sum(partition), 100);
+        assert_eq!(partition(0)(), 0);
+        assert_eq!(sum(impl_fn), sum(box_dyn_fn) - 10 * 100);
+        assert_eq!(sum(fn_pointer), sum(generic));
+        assert_eq!(sum(dyn_draw), 100 * 114); // the section's screen is 114 bytes
+        assert_eq!(sum(generic_draw), sum(dyn_draw));
+        assert_eq!(sum(enum_draw), sum(dyn_draw));
+    }
+
+    #[test]
+    fn table_has_a_row_per_alternative_and_size() {
+        let config = Config { sizes: vec![1, 20], samples: 2, sample_time: Duration::from_micros(50) };
+        let topic = topic("iterating-errors").unwrap();
+        let results = run(topic, &config);
+        assert_eq!(results.len(), 6);
+        assert!(results.iter().all(|m| m.ns_per_iter > 0.0 && m.deviation >= 0.0));
+
+        let table = table(topic, &results);
+        assert!(table.starts_with("iterating-errors: filter_map() vs"));
+        assert_eq!(table.lines().filter(|line| line.starts_with("partition ")).count(), 2);
+        assert_eq!(mean_and_deviation(&[1.0, 3.0]), (2.0, 1.0));
+    }
+}
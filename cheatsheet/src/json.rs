@@ -0,0 +1,188 @@
+// Just enough JSON to read rustc's `--error-format=json` diagnostics
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl Json {
+    pub(crate) fn parse(text: &str) -> Option<Json> {
+        let mut parser = Parser { text: text.as_bytes(), pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        (parser.pos == parser.text.len()).then_some(value)
+    }
+
+    /// `value["key"]`, `Null` when missing or not an object.
+    pub(crate) fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(map) => map.get(key).unwrap_or(&Json::Null),
+            _ => &Json::Null,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(n) if *n >= 0.0 => Some(*n as u64),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.text.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, literal: &str) -> Option<()> {
+        let end = self.pos + literal.len();
+        (self.text.get(self.pos..end)? == literal.as_bytes()).then(|| self.pos = end)
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_whitespace();
+        match *self.text.get(self.pos)? {
+            b'n' => self.eat("null").map(|_| Json::Null),
+            b't' => self.eat("true").map(|_| Json::Bool(true)),
+            b'f' => self.eat("false").map(|_| Json::Bool(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.eat("]").is_some() {
+                    return Some(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    if self.eat("]").is_some() {
+                        return Some(Json::Array(items));
+                    }
+                    self.eat(",")?;
+                }
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut map = BTreeMap::new();
+                self.skip_whitespace();
+                if self.eat("}").is_some() {
+                    return Some(Json::Object(map));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    self.eat(":")?;
+                    map.insert(key, self.value()?);
+                    self.skip_whitespace();
+                    if self.eat("}").is_some() {
+                        return Some(Json::Object(map));
+                    }
+                    self.eat(",")?;
+                }
+            }
+            _ => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Option<Json> {
+        let start = self.pos;
+        while self.text.get(self.pos).is_some_and(|c| b"+-.eE0123456789".contains(c)) {
+            self.pos += 1;
+        }
+        let number = std::str::from_utf8(&self.text[start..self.pos]).ok()?;
+        number.parse().ok().map(Json::Number)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.eat("\"")?;
+        let mut bytes = Vec::new();
+        loop {
+            match *self.text.get(self.pos)? {
+                b'"' => break,
+                b'\\' => {
+                    self.pos += 1;
+                    let escaped = match *self.text.get(self.pos)? {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => {
+                            let hex = std::str::from_utf8(self.text.get(self.pos + 1..self.pos + 5)?).ok()?;
+                            self.pos += 4;
+                            // Surrogate pairs are not needed for diagnostics
+                            char::from_u32(u32::from_str_radix(hex, 16).ok()?).unwrap_or('\u{fffd}')
+                        }
+                        c => c as char,
+                    };
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                c => bytes.push(c),
+            }
+            self.pos += 1;
+        }
+        self.pos += 1;
+        String::from_utf8(bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_diagnostic() {
+        let line = r#"{"message":"unused \"x\"\n","code":{"code":"dead_code"},"spans":[{"line_start":5,"is_primary":true}],"rendered":null}"#;
+        let json = Json::parse(line).unwrap();
+        assert_eq!(json.get("message").as_str(), Some("unused \"x\"\n"));
+        assert_eq!(json.get("code").get("code").as_str(), Some("dead_code"));
+        let span = &json.get("spans").as_array()[0];
+        assert_eq!(span.get("line_start").as_u64(), Some(5));
+        assert_eq!(span.get("is_primary").as_bool(), Some(true));
+        assert_eq!(json.get("rendered"), &Json::Null);
+        assert_eq!(json.get("missing"), &Json::Null);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert_eq!(Json::parse(r#"{"a": [1, 2"#), None);
+        assert_eq!(Json::parse(r#"{"a": 1} x"#), None);
+        assert_eq!(Json::parse("[-1.5e3, true, \"\\u00e9\"]").unwrap().as_array()[2].as_str(), Some("é"));
+    }
+}
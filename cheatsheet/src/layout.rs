@@ -0,0 +1,360 @@
+//! Stack and heap diagrams for the annotated mode of the smart pointer
+//! sections (`cheatsheet annotate box`).
+//!
+//! Heap allocations are numbered so that exported docs are the same on
+//! every run. The alternate format (`{:#}`), and [`show`](Diagram::show)
+//! inside [`with_addresses`], print the real addresses next to the numbers.
+//!
+//! ```
+//! use cheatsheet::layout::Diagram;
+//! use std::rc::Rc;
+//!
+//! let a = Rc::new(5);
+//! let b = Rc::clone(&a);
+//! let diagram = Diagram::new("let b = Rc::clone(&a);").rc("a", &a).rc("b", &b).to_string();
+//! assert!(diagram.contains("strong = 2"));
+//! ```
+use crate::trace;
+use std::any;
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Debug};
+use std::mem::size_of;
+use std::rc::Rc;
+
+/// Boxes for the stack slots of some smart pointers, with arrows to the
+/// heap allocations they point at.
+#[derive(Debug)]
+pub struct Diagram {
+    caption: String,
+    stack: Vec<Frame>,
+    heap: Vec<Frame>,
+}
+
+#[derive(Debug)]
+struct Frame {
+    lines: Vec<String>,
+    // Stack frames: index of the heap frame pointed at, drawn as line 1.
+    // Heap frames: the address, so two Rcs share one allocation
+    pointer: Option<usize>,
+    address: usize,
+}
+
+thread_local! {
+    static ADDRESSES: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with the diagrams it shows printing addresses, as
+/// `cheatsheet annotate` does.
+pub fn with_addresses<R>(f: impl FnOnce() -> R) -> R {
+    struct Reset(bool);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            ADDRESSES.with(|addresses| addresses.set(self.0));
+        }
+    }
+    let _reset = Reset(ADDRESSES.with(|addresses| addresses.replace(true)));
+    f()
+}
+
+impl Diagram {
+    pub fn new(caption: &str) -> Self {
+        Diagram { caption: caption.to_owned(), stack: Vec::new(), heap: Vec::new() }
+    }
+
+    /// `name: Box<T>`, a pointer to a heap allocation holding the value.
+    #[allow(clippy::borrowed_box)] // the Box itself is what is drawn
+    pub fn boxed<T: Debug>(self, name: &str, boxed: &Box<T>) -> Self {
+        let address = &**boxed as *const T as usize;
+        let heap = vec![format!("{:?}", **boxed), format!("size_of = {}", size_of::<T>())];
+        self.pointer(name, type_name::<Box<T>>(), size_of::<Box<T>>(), address, heap)
+    }
+
+    /// `name: Rc<T>`, a pointer to a heap allocation holding the two
+    /// reference counts next to the value.
+    pub fn rc<T: Debug>(self, name: &str, rc: &Rc<T>) -> Self {
+        let heap = vec![
+            format!("strong = {}", Rc::strong_count(rc)),
+            format!("weak = {}", Rc::weak_count(rc)),
+            format!("value = {:?}", **rc),
+        ];
+        self.pointer(name, type_name::<Rc<T>>(), size_of::<Rc<T>>(), Rc::as_ptr(rc) as usize, heap)
+    }
+
+    /// `name: Rc<RefCell<T>>`, like [`Diagram::rc`] with the borrow state of
+    /// the shared cell.
+    pub fn rc_refcell<T: Debug>(self, name: &str, rc: &Rc<RefCell<T>>) -> Self {
+        let heap = vec![
+            format!("strong = {}", Rc::strong_count(rc)),
+            format!("weak = {}", Rc::weak_count(rc)),
+            format!("borrow = {}", borrow_state(rc)),
+            format!("value = {}", cell_value(rc)),
+        ];
+        let ty = type_name::<Rc<RefCell<T>>>();
+        self.pointer(name, ty, size_of::<Rc<RefCell<T>>>(), Rc::as_ptr(rc) as usize, heap)
+    }
+
+    /// `name: RefCell<T>`, stored inline: the borrow flag sits next to the
+    /// value on the stack.
+    pub fn refcell<T: Debug>(mut self, name: &str, cell: &RefCell<T>) -> Self {
+        self.stack.push(Frame {
+            lines: vec![
+                format!("{name}: {}", type_name::<RefCell<T>>()),
+                format!("borrow = {}", borrow_state(cell)),
+                format!("value = {}", cell_value(cell)),
+                format!("size_of = {}", size_of::<RefCell<T>>()),
+            ],
+            pointer: None,
+            address: cell as *const RefCell<T> as usize,
+        });
+        self
+    }
+
+    fn pointer(mut self, name: &str, ty: String, size: usize, address: usize, heap: Vec<String>) -> Self {
+        let target = match self.heap.iter().position(|frame| frame.address == address) {
+            Some(index) => index,
+            None => {
+                self.heap.push(Frame { lines: heap, pointer: None, address });
+                self.heap.len() - 1
+            }
+        };
+        self.stack.push(Frame {
+            lines: vec![format!("{name}: {ty}"), format!("size_of = {size}")],
+            pointer: Some(target),
+            address: 0,
+        });
+        self
+    }
+
+    /// Adds the diagram to the annotated timeline, with addresses inside
+    /// [`with_addresses`].
+    pub fn show(self) {
+        match ADDRESSES.with(Cell::get) {
+            true => trace::note(format!("{self:#}")),
+            false => trace::note(self.to_string()),
+        }
+    }
+
+    // The lines of a frame with its allocation number, `#1 at 0x...` with
+    // `addresses`
+    fn lines(&self, frame: &Frame, heap: bool, addresses: bool) -> Vec<String> {
+        let label = |index: usize| match addresses {
+            true => format!("#{} at {:#x}", index + 1, self.heap[index].address),
+            false => format!("#{}", index + 1),
+        };
+        let mut lines = frame.lines.clone();
+        if heap {
+            let index = self.heap.iter().position(|h| h.address == frame.address).unwrap();
+            lines.insert(0, label(index));
+        } else if let Some(target) = frame.pointer {
+            lines.insert(1, format!("ptr = {}", label(target)));
+        }
+        lines
+    }
+}
+
+// `alloc::rc::Rc<core::cell::RefCell<i32>>` -> `Rc<RefCell<i32>>`
+fn type_name<T: ?Sized>() -> String {
+    let full = any::type_name::<T>();
+    let mut short = String::new();
+    let mut segment = String::new();
+    for c in full.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap());
+            segment.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(segment.rsplit("::").next().unwrap());
+    short
+}
+
+// RefCell only tells whether borrowing would succeed, not how many Refs exist
+fn borrow_state<T>(cell: &RefCell<T>) -> &'static str {
+    if cell.try_borrow_mut().is_ok() {
+        "none"
+    } else if cell.try_borrow().is_ok() {
+        "shared (Ref)"
+    } else {
+        "exclusive (RefMut)"
+    }
+}
+
+fn cell_value<T: Debug>(cell: &RefCell<T>) -> String {
+    match cell.try_borrow() {
+        Ok(value) => format!("{:?}", *value),
+        Err(_) => "<mutably borrowed>".to_owned(),
+    }
+}
+
+/* ---------------------------------------------------------------------- */
+/* ---------------------------    Drawing    ---------------------------- */
+/* ---------------------------------------------------------------------- */
+
+// Columns between the stack and the heap, the arrows join halfway
+const GAP: usize = 10;
+
+struct Canvas {
+    rows: Vec<Vec<char>>,
+}
+
+impl Canvas {
+    fn put(&mut self, x: usize, y: usize, c: char) {
+        if self.rows.len() <= y {
+            self.rows.resize(y + 1, Vec::new());
+        }
+        let row = &mut self.rows[y];
+        if row.len() <= x {
+            row.resize(x + 1, ' ');
+        }
+        // Crossing lines join
+        row[x] = match (row[x], c) {
+            ('-', '|') | ('|', '-') => '+',
+            ('+', _) => '+',
+            _ => c,
+        };
+    }
+
+    fn text(&mut self, x: usize, y: usize, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            self.put(x + i, y, c);
+        }
+    }
+
+    fn frame(&mut self, x: usize, y: usize, width: usize, lines: &[String]) {
+        let border = format!("+{}+", "-".repeat(width - 2));
+        self.text(x, y, &border);
+        for (i, line) in lines.iter().enumerate() {
+            self.text(x, y + 1 + i, &format!("| {line:<inner$} |", inner = width - 4));
+        }
+        self.text(x, y + 1 + lines.len(), &border);
+    }
+}
+
+fn width(frames: &[Vec<String>]) -> usize {
+    frames.iter().flatten().map(|l| l.chars().count()).max().unwrap_or(0) + 4
+}
+
+impl fmt::Display for Diagram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stack: Vec<Vec<String>> = self.stack.iter().map(|frame| self.lines(frame, false, f.alternate())).collect();
+        let heap: Vec<Vec<String>> = self.heap.iter().map(|frame| self.lines(frame, true, f.alternate())).collect();
+
+        let mut canvas = Canvas { rows: Vec::new() };
+        let stack_width = width(&stack);
+        let heap_x = stack_width + GAP;
+        let join_x = stack_width + GAP / 2;
+
+        canvas.text(0, 0, "stack");
+        if !self.heap.is_empty() {
+            canvas.text(heap_x, 0, "heap");
+        }
+
+        // Stack frames top to bottom, remembering the row of each pointer
+        let mut y = 1;
+        let mut arrows = Vec::new();
+        for (frame, lines) in self.stack.iter().zip(&stack) {
+            canvas.frame(0, y, stack_width, lines);
+            if let Some(target) = frame.pointer {
+                arrows.push((y + 2, target));
+            }
+            y += lines.len() + 2;
+        }
+
+        // Each heap frame next to the first pointer at it, without overlaps
+        let mut heap_rows = Vec::new();
+        let mut free = 1;
+        for (index, lines) in heap.iter().enumerate() {
+            let first = arrows.iter().find(|(_, target)| *target == index).map_or(free, |(row, _)| *row);
+            let top = first.saturating_sub(1).max(free);
+            canvas.frame(heap_x, top, width(&heap), lines);
+            heap_rows.push(top + 1);
+            free = top + lines.len() + 2;
+        }
+
+        for (row, target) in arrows {
+            let to = heap_rows[target];
+            if row == to {
+                canvas.text(stack_width, row, &"-".repeat(heap_x - stack_width - 1));
+            } else {
+                canvas.text(stack_width, row, &"-".repeat(join_x - stack_width));
+                for y in row.min(to)..=row.max(to) {
+                    canvas.put(join_x, y, '|');
+                }
+                canvas.put(join_x, row, '+');
+                canvas.put(join_x, to, '+');
+                canvas.text(join_x + 1, to, &"-".repeat(heap_x - join_x - 2));
+            }
+            canvas.put(heap_x - 1, to, '>');
+        }
+
+        writeln!(f, "{}", self.caption)?;
+        for row in &canvas.rows {
+            let line: String = row.iter().collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_type_names() {
+        assert_eq!(type_name::<Rc<RefCell<i32>>>(), "Rc<RefCell<i32>>");
+        assert_eq!(type_name::<Box<String>>(), "Box<String>");
+        assert_eq!(type_name::<Option<(u8, &str)>>(), "Option<(u8, &str)>");
+    }
+
+    #[test]
+    fn clones_of_an_rc_share_one_heap_frame() {
+        let a = Rc::new(5);
+        let b = Rc::clone(&a);
+        let diagram = Diagram::new("two owners").rc("a", &a).rc("b", &b);
+        assert_eq!(diagram.heap.len(), 1);
+
+        let text = diagram.to_string();
+        assert!(text.starts_with("two owners\nstack"));
+        assert!(text.contains("| ptr = #1    |-----+--->| #1"));
+        assert_eq!(text, Diagram::new("two owners").rc("a", &a).rc("b", &b).to_string());
+        assert!(text.contains("| b: Rc<i32>"));
+        // Corners of the three frames, and where the arrow of b joins a's
+        assert_eq!(text.matches('+').count(), 4 * 3 + 2);
+    }
+
+    #[test]
+    fn addresses_next_to_the_numbers() {
+        let a = Rc::new(5);
+        let address = format!("#1 at {:#x}", Rc::as_ptr(&a) as usize);
+        let diagram = || Diagram::new("").rc("a", &a);
+        assert!(!diagram().to_string().contains(" at 0x"));
+        let text = format!("{:#}", diagram());
+        assert!(text.contains(&format!("| ptr = {address} |")));
+        assert!(text.contains(&format!("| {address} |")));
+
+        let note = |events: Vec<trace::Event>| match &events[..] {
+            [trace::Event::Note(text)] => text.clone(),
+            _ => panic!("one note expected"),
+        };
+        assert_eq!(note(trace::record(|| diagram().show())), diagram().to_string());
+        assert_eq!(note(with_addresses(|| trace::record(|| diagram().show()))), text);
+        assert_eq!(note(trace::record(|| diagram().show())), diagram().to_string());
+    }
+
+    #[test]
+    fn refcell_shows_the_borrow_state() {
+        let cell = RefCell::new(5);
+        let shared = cell.borrow();
+        assert!(Diagram::new("").refcell("c", &cell).to_string().contains("borrow = shared (Ref)"));
+        drop(shared);
+        let exclusive = cell.borrow_mut();
+        let text = Diagram::new("").refcell("c", &cell).to_string();
+        assert!(text.contains("borrow = exclusive (RefMut)"));
+        assert!(text.contains("value = <mutably borrowed>"));
+        drop(exclusive);
+        assert!(Diagram::new("").refcell("c", &cell).to_string().contains("borrow = none"));
+    }
+}
@@ -0,0 +1,226 @@
+/* Rust cheatsheet from https://letsgetrusty.com/ */
+//! The Rust cheatsheet as a library: a registry of runnable sections grouped
+//! into categories, plus renderers for the whole document.
+//!
+//! ```
+//! use cheatsheet::Cheatsheet;
+//!
+//! let section = Cheatsheet::section("loop").unwrap();
+//! assert_eq!(section.title(), "Loop");
+//! assert_eq!(section.output(), "loop count is: 5\n");
+//! ```
+//!
+//! Sections are authored as `content/<category>/<section>.md` and compiled
+//! into the registry by `build.rs`: the front matter gives the title, tags,
+//! see-also links and expected output, the ```` ```rust ```` blocks become the
+//! body of the section.
+//!
+//! A few examples need to be reachable from doc tests and from the
+//! integration tests in `tests/`, so they live at the crate root.
+
+pub mod asm;
+pub mod audit;
+pub mod bench;
+mod json;
+pub mod layout;
+pub mod lint;
+mod out;
+pub mod render;
+mod section;
+pub mod trace;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+mod sections;
+
+pub use section::{Category, DisabledCategory, Section};
+
+/// Entry point to the registry of categories and sections.
+pub struct Cheatsheet;
+
+impl Cheatsheet {
+    /// All categories, in cheatsheet order.
+    pub fn categories() -> &'static [Category] {
+        sections::CATEGORIES
+    }
+
+    /// All sections of all categories, in cheatsheet order.
+    pub fn sections() -> impl Iterator<Item = &'static Section> {
+        Self::categories().iter().flat_map(Category::sections)
+    }
+
+    /// Categories compiled out of this build by cargo features.
+    pub fn disabled_categories() -> &'static [DisabledCategory] {
+        sections::DISABLED
+    }
+
+    pub fn category(id: &str) -> Option<&'static Category> {
+        Self::categories().iter().find(|c| c.id() == id)
+    }
+
+    pub fn section(id: &str) -> Option<&'static Section> {
+        Self::sections().find(|s| s.id() == id)
+    }
+}
+
+/* ---------------------       Trait Bounds       ----------------------- */
+
+/// Returns the largest item of `list`.
+///
+/// `T: PartialOrd` allows comparing with `>`, `T: Copy` allows moving
+/// items out of the slice by value.
+///
+/// ```
+/// use cheatsheet::largest;
+///
+/// assert_eq!(largest(&[34, 50, 25, 100, 65]), 100);
+/// assert_eq!(largest(&['y', 'm', 'a', 'q']), 'y');
+/// ```
+///
+/// Panics if `list` is empty:
+///
+/// ```should_panic
+/// cheatsheet::largest::<i32>(&[]);
+/// ```
+pub fn largest<T: PartialOrd + Copy>(list: &[T]) -> T {
+    let mut largest = list[0];
+    for &item in list {
+        if item > largest {
+            largest = item;
+        }
+    }
+    largest
+}
+
+/* ----------------      Defining & using Modules     ------------------- */
+
+/// Called from `inner_module` through a `super::super::` path.
+pub fn some_function() -> String {
+    format!("some_function -> {}", my_module::my_function())
+}
+
+mod outer_module { // private module
+    pub mod inner_module { // public module
+        pub fn inner_public_function() -> String {
+            // private items are visible inside their own module
+            inner_private_function();
+            format!("inner_public_function -> {}", super::super::some_function())
+        }
+
+        fn inner_private_function() {}
+    }
+}
+
+/// Calls `inner_public_function` through every kind of path. Returns one
+/// line per call.
+///
+/// `outer_module` is private, other crates (like the cheatsheet binary) can
+/// only reach `inner_module` through the `pub use` re-export:
+///
+/// ```
+/// let trace = cheatsheet::inner_module::inner_public_function();
+/// assert!(trace.ends_with("my_module::my_function"));
+/// ```
+///
+/// Private items can't be called from outside, even through a re-export:
+///
+/// ```compile_fail,E0603
+/// cheatsheet::inner_module::inner_private_function(); // function is private
+/// ```
+///
+/// ```compile_fail,E0603
+/// cheatsheet::outer_module::inner_module::inner_public_function(); // module is private
+/// ```
+pub fn module_paths() -> Vec<String> {
+    // absolute path
+    let absolute = crate::outer_module::inner_module::inner_public_function();
+
+    // relative path
+    let relative = outer_module::inner_module::inner_public_function();
+
+    // bringing path into scope
+    use outer_module::inner_module;
+    let in_scope = inner_module::inner_public_function();
+
+    vec![absolute, relative, in_scope]
+}
+
+/* --------------     Re-exporting with 'pub use'     ------------------- */
+pub use crate::outer_module::inner_module;
+
+/* -----------    Defining modules in separate files     ---------------- */
+// Loads the module from src/my_module.rs
+mod my_module;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::num::ParseIntError;
+
+    #[test]
+    fn ids_are_unique() {
+        let mut ids = HashSet::new();
+        for category in Cheatsheet::categories() {
+            assert!(ids.insert(category.id()), "duplicate id {}", category.id());
+        }
+        for section in Cheatsheet::sections() {
+            assert!(ids.insert(section.id()), "duplicate id {}", section.id());
+        }
+        for disabled in Cheatsheet::disabled_categories() {
+            assert!(ids.insert(disabled.id()), "duplicate id {}", disabled.id());
+        }
+    }
+
+    #[test]
+    fn lookup_by_id() {
+        assert_eq!(Cheatsheet::section("tuple").map(Section::title), Some("Tuple"));
+        assert_eq!(Cheatsheet::category("collections").map(|c| c.sections().len()), Some(6));
+        assert!(Cheatsheet::section("no-such-section").is_none());
+    }
+
+    #[test]
+    fn every_module_path_reaches_the_same_function() {
+        let paths = module_paths();
+        assert_eq!(paths.len(), 3);
+        for trace in paths {
+            assert_eq!(
+                trace,
+                "inner_public_function -> some_function -> my_module::my_function"
+            );
+        }
+    }
+
+    #[test]
+    fn largest_of_numbers() {
+        assert_eq!(largest(&[34, 50, 25, 100, 65]), 100);
+    }
+
+    #[test]
+    fn largest_of_floats_and_chars() {
+        assert_eq!(largest(&[0.5, -1.0, 0.25]), 0.5);
+        assert_eq!(largest(&['y', 'm', 'a', 'q']), 'y');
+    }
+
+    #[test]
+    fn largest_keeps_first_of_equal_items() {
+        let pairs = [(1, 'a'), (2, 'b'), (2, 'a')];
+        assert_eq!(largest(&pairs), (2, 'b'));
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn largest_of_empty_slice_panics() {
+        largest::<u8>(&[]);
+    }
+
+    #[test]
+    fn largest_of_parsed_input() -> Result<(), ParseIntError> {
+        let numbers = "7 42 3"
+            .split(' ')
+            .map(str::parse)
+            .collect::<Result<Vec<i32>, _>>()?;
+        assert_eq!(largest(&numbers), 42);
+        Ok(())
+    }
+}
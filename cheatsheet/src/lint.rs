@@ -0,0 +1,268 @@
+//! Consistency checks over the content of every section, run by
+//! `cheatsheet lint`.
+use crate::{Cheatsheet, Section};
+use std::collections::HashSet;
+use std::fmt;
+
+// One word per line, lowercase. Add new words here when the spell check
+// trips over a correctly spelled one.
+const DICTIONARY: &str = include_str!("../content/dictionary.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+/// A problem found in one category or section.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub level: Level,
+    /// Id of the category or section the problem is in.
+    pub id: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.level {
+            Level::Warning => "warning",
+            Level::Error => "error",
+        };
+        write!(f, "{level}[{}]: {}", self.id, self.message)
+    }
+}
+
+/// Runs every check over every category and section compiled into this
+/// build. Sections are run to compare their output.
+pub fn lint() -> Vec<Diagnostic> {
+    let dictionary: HashSet<&str> = DICTIONARY.lines().map(str::trim).collect();
+    let mut diagnostics = Vec::new();
+    let mut report = |level, id, message: String| diagnostics.push(Diagnostic { level, id, message });
+
+    let mut ids = HashSet::new();
+    let disabled = Cheatsheet::disabled_categories().iter().map(|c| c.id());
+    let categories = Cheatsheet::categories().iter().map(|c| c.id());
+    for id in categories.chain(disabled).chain(Cheatsheet::sections().map(Section::id)) {
+        if !ids.insert(id) {
+            report(Level::Error, id, "id is used more than once".to_owned());
+        }
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+            report(Level::Error, id, "id is not kebab-case".to_owned());
+        }
+    }
+
+    for category in Cheatsheet::categories() {
+        if category.title().trim().is_empty() {
+            report(Level::Error, category.id(), "missing title".to_owned());
+        }
+        for word in misspelled(&dictionary, category.title()).chain(misspelled_markdown(&dictionary, category.markdown())) {
+            report(Level::Error, category.id(), format!("unknown word `{word}`"));
+        }
+    }
+
+    for section in Cheatsheet::sections() {
+        let id = section.id();
+        if section.title().trim().is_empty() {
+            report(Level::Error, id, "missing title".to_owned());
+        }
+
+        let output = section.output();
+        match (section.expected_output(), section.no_output()) {
+            (Some(_), true) => report(Level::Error, id, "has both expected-output and no-output".to_owned()),
+            (None | Some(""), false) => {
+                report(Level::Error, id, "missing expected-output, or `no-output: true` if it prints nothing".to_owned())
+            }
+            (Some(expected), false) if expected != output => {
+                report(Level::Error, id, format!("prints {output:?}, expected-output is {expected:?}"))
+            }
+            (None, true) if !output.is_empty() => {
+                report(Level::Error, id, format!("marked no-output but prints {output:?}"))
+            }
+            _ => {}
+        }
+        if let Some(desugared) = section.desugar() {
+            if desugared != output {
+                report(Level::Error, id, format!("desugared code prints {desugared:?}, the section prints {output:?}"));
+            }
+        }
+
+        for word in misspelled(&dictionary, section.title()).chain(misspelled_markdown(&dictionary, section.markdown())) {
+            report(Level::Error, id, format!("unknown word `{word}`"));
+        }
+
+        for item in unused_items(&format!("{}{}", section.module_code(), section.code())) {
+            report(Level::Warning, id, format!("`{item}` is declared but never used"));
+        }
+    }
+    diagnostics
+}
+
+/* ---------------------------------------------------------------------- */
+/* --------------------------    Spelling    ---------------------------- */
+/* ---------------------------------------------------------------------- */
+
+// Prose, ```text blocks and the comments of ```rust blocks are checked,
+// `inline code` and shell snippets are not
+fn misspelled_markdown<'a>(dictionary: &'a HashSet<&str>, markdown: &'a str) -> impl Iterator<Item = String> + 'a {
+    let mut fence: Option<&str> = None;
+    let mut checked = Vec::new();
+    for line in markdown.lines() {
+        match fence {
+            None => match line.strip_prefix("```") {
+                Some(info) => fence = Some(info.trim()),
+                None => checked.push(line),
+            },
+            Some(_) if line.trim_end() == "```" => fence = None,
+            Some("text") => checked.push(line),
+            Some(info) if info.starts_with("rust") => {
+                if let Some((_, comment)) = line.split_once("// ") {
+                    checked.push(comment);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    checked.into_iter().flat_map(move |text| misspelled(dictionary, text))
+}
+
+fn misspelled<'a>(dictionary: &'a HashSet<&str>, text: &'a str) -> impl Iterator<Item = String> + 'a {
+    words(text)
+        .map(str::to_lowercase)
+        .filter(|word| !dictionary.contains(word.as_str()))
+}
+
+// Plain words of `text`. Anything that looks like code is skipped: inline
+// `code`, identifiers with '_', '::', digits or brackets, CamelCase and
+// ACRONYMS
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split('`')
+        .step_by(2)
+        .flat_map(str::split_whitespace)
+        .filter(|chunk| {
+            !chunk.contains(|c: char| c == '_' || c == ':' || c.is_ascii_digit() || "()[]{}<>=&*/\\#$@".contains(c))
+        })
+        .flat_map(|chunk| chunk.split(|c: char| !c.is_alphabetic()))
+        .filter(|word| word.chars().count() > 1 && !word.chars().skip(1).any(char::is_uppercase))
+}
+
+/* ---------------------------------------------------------------------- */
+/* ------------------------    Unused items    -------------------------- */
+/* ---------------------------------------------------------------------- */
+
+// Items the section declares and never mentions again. Sections may allow
+// `dead_code` in their front matter, so rustc won't always point these out,
+// and a leading '_' would hide them anyway. Items of traits and trait impls, tests and
+// functions exported to C are used from elsewhere and are skipped.
+fn unused_items(code: &str) -> Vec<String> {
+    fn tokens(line: &str) -> impl Iterator<Item = &str> {
+        line.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '\''))
+            .filter(|token| !token.is_empty())
+    }
+
+    let lines = code_lines(code);
+    let all: Vec<&str> = lines.iter().flat_map(|line| tokens(line)).collect();
+
+    let mut unused = Vec::new();
+    let mut depth = 0;
+    let mut skip_from: Option<i32> = None;
+    let mut skip_next = false;
+    for line in &lines {
+        let trimmed = line.trim_start().trim_start_matches("pub ");
+        let is_trait = trimmed.starts_with("trait ") || (trimmed.starts_with("impl") && trimmed.contains(" for "));
+        if skip_from.is_none() && is_trait {
+            skip_from = Some(depth);
+        }
+        if skip_from.is_none() && !skip_next {
+            let line_tokens: Vec<&str> = tokens(line).filter(|t| *t != "mut").collect();
+            for pair in line_tokens.windows(2) {
+                let [keyword, name] = pair else { unreachable!() };
+                let declares = matches!(*keyword, "struct" | "enum" | "trait" | "fn" | "type" | "const" | "static");
+                if declares && !unused.iter().any(|u| u == name) && all.iter().filter(|t| *t == name).count() == 1 {
+                    unused.push(name.to_string());
+                }
+            }
+        }
+
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        if skip_from.is_some_and(|from| depth <= from) {
+            skip_from = None;
+        }
+        let attribute = line.trim_start();
+        skip_next = attribute.starts_with("#[") && (attribute.contains("test") || attribute.contains("no_mangle"))
+            || skip_next && attribute.starts_with("#[");
+    }
+    unused
+}
+
+// The lines of `code` without comments and with the contents of string
+// literals removed, so `println!("an enum thing")` declares nothing
+fn code_lines(code: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_string = false;
+    for line in code.lines() {
+        let mut kept = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if in_string => {
+                    chars.next();
+                }
+                '"' => {
+                    in_string = !in_string;
+                    kept.push(c);
+                }
+                _ if in_string => {}
+                // '"' is a char, not the start of a string
+                '\'' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    kept.push_str("'\"");
+                }
+                '/' if chars.peek() == Some(&'/') => break,
+                _ => kept.push(c),
+            }
+        }
+        lines.push(kept);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_skip_code() {
+        let text = "Each value has an `owner`, see iter_mut() and RefCell; UTF-8 isn't a u8";
+        assert_eq!(words(text).collect::<Vec<_>>(), ["Each", "value", "has", "an", "see", "and", "isn"]);
+    }
+
+    #[test]
+    fn unused_items_ignore_comments_and_used_items() {
+        let code = "\
+struct _Cacher { f: u32 }
+// _Cacher::new
+static mut COUNT: u32 = 0;
+fn used() -> &'static str { \"\" }
+fn main() { used(); }
+fn print() { println!(\"an enum thing, \\\"a struct\\\" // fn too\"); let _q = '\"'; }
+impl Iterator for Counter {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> { None }
+}
+#[test]
+fn is_a_test() {}
+";
+        assert_eq!(unused_items(code), ["_Cacher", "COUNT", "main", "print"]);
+    }
+
+    #[test]
+    fn content_has_no_errors() {
+        let errors: Vec<String> = lint()
+            .iter()
+            .filter(|d| d.level == Level::Error)
+            .map(Diagnostic::to_string)
+            .collect();
+        assert!(errors.is_empty(), "{}", errors.join("\n"));
+    }
+}
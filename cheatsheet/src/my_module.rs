@@ -0,0 +1,6 @@
+// Module declared with `mod my_module;` in src/lib.rs
+
+// pub, so the parent module (the crate root) can call it
+pub fn my_function() -> &'static str {
+    "my_module::my_function"
+}
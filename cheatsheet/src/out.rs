@@ -0,0 +1,87 @@
+// print!/println! replacements for the sections, so their output can be
+// captured for rendering. Outside of `capture` they print to stdout as usual.
+use std::cell::RefCell;
+use std::fmt;
+use std::io::Write;
+
+thread_local! {
+    // One buffer per nested `capture` call
+    static CAPTURED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::out::emit(format_args!($($arg)*))
+    };
+}
+
+macro_rules! println {
+    () => {
+        $crate::out::emit(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {{
+        $crate::out::emit(format_args!($($arg)*));
+        $crate::out::emit(format_args!("\n"));
+    }};
+}
+
+pub(crate) use {print, println};
+
+pub(crate) fn emit(args: fmt::Arguments) {
+    // Format before borrowing, a Display impl could print as well
+    let text = args.to_string();
+    let printed = CAPTURED.with(|captured| match captured.borrow_mut().last_mut() {
+        Some(buf) => {
+            buf.push_str(&text);
+            true
+        }
+        None => false,
+    });
+    if !printed {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(text.as_bytes()).unwrap();
+    }
+}
+
+/// Runs `f` and returns everything it printed through the macros above.
+pub(crate) fn capture(f: impl FnOnce()) -> String {
+    // Pops the buffer even if `f` panics
+    struct Guard;
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            CAPTURED.with(|captured| captured.borrow_mut().pop());
+        }
+    }
+
+    CAPTURED.with(|captured| captured.borrow_mut().push(String::new()));
+    let guard = Guard;
+    f();
+    let output = CAPTURED.with(|captured| captured.borrow_mut().last_mut().map(std::mem::take));
+    drop(guard);
+    output.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_collects_output() {
+        let output = capture(|| {
+            println!("hello {}", 1);
+            print!("a");
+            println!();
+        });
+        assert_eq!(output, "hello 1\na\n");
+    }
+
+    #[test]
+    fn nested_captures_are_separate() {
+        let mut inner = String::new();
+        let outer = capture(|| {
+            print!("outer ");
+            inner = capture(|| print!("inner"));
+        });
+        assert_eq!((outer.as_str(), inner.as_str()), ("outer ", "inner"));
+    }
+}
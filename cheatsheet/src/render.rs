@@ -0,0 +1,215 @@
+//! Renders the whole cheatsheet as a document.
+use crate::{trace, Category, Cheatsheet, Section};
+use std::fmt::Write;
+
+/// Renders every category and section as GitHub flavoured Markdown: the
+/// prose and code from `content/`, and the output of each section captured by
+/// running it.
+pub fn markdown() -> String {
+    let mut md = String::from("# Rust cheatsheet\n\n");
+    for category in Cheatsheet::categories() {
+        writeln!(md, "- [{}](#{})", category.title(), anchor(category.title())).unwrap();
+    }
+    for category in Cheatsheet::categories() {
+        md.push('\n');
+        markdown_category(&mut md, category);
+    }
+    md
+}
+
+fn markdown_category(md: &mut String, category: &Category) {
+    writeln!(md, "## {}", category.title()).unwrap();
+    if !category.markdown().is_empty() {
+        writeln!(md, "\n{}", markdown_body(category.markdown())).unwrap();
+    }
+    for section in category.sections() {
+        md.push('\n');
+        markdown_section(md, section);
+    }
+}
+
+fn markdown_section(md: &mut String, section: &Section) {
+    writeln!(md, "### {}\n", section.title()).unwrap();
+    writeln!(md, "`cheatsheet run {}`", section.id()).unwrap();
+    if !section.markdown().is_empty() {
+        writeln!(md, "\n{}", markdown_body(section.markdown())).unwrap();
+    }
+    let output = section.output();
+    if !output.is_empty() {
+        writeln!(md, "\n```text\n{}```", output).unwrap();
+    }
+    if let Some(events) = section.annotate() {
+        writeln!(md, "\n`cheatsheet annotate {}`\n\n```text\n{}```", section.id(), trace::timeline(&events)).unwrap();
+    }
+    let links: Vec<String> = see_also(section)
+        .map(|other| format!("[{}](#{})", other.title(), anchor(other.title())))
+        .collect();
+    if !links.is_empty() {
+        writeln!(md, "\nSee also: {}", links.join(", ")).unwrap();
+    }
+}
+
+// ```rust,module and ```rust,desugared are our own info strings, GitHub only
+// highlights ```rust. The instrumented code of ```rust,annotated is shown as a timeline instead
+fn markdown_body(body: &str) -> String {
+    let mut md = String::new();
+    let mut lines = body.lines();
+    while let Some(line) = lines.next() {
+        if line == "```rust,annotated" {
+            lines.by_ref().take_while(|l| !l.starts_with("```")).for_each(drop);
+            continue;
+        }
+        let own = line.strip_prefix("```rust,module").or_else(|| line.strip_prefix("```rust,desugared"));
+        md.push_str(own.map_or(line, |_| "```rust"));
+        md.push('\n');
+    }
+    md.trim_end().replace("\n\n\n", "\n\n")
+}
+
+fn see_also(section: &Section) -> impl Iterator<Item = &'static Section> {
+    // Sections of a disabled category are silently left out
+    section.see_also().iter().filter_map(|id| Cheatsheet::section(id))
+}
+
+/// Renders the cheatsheet as a single self-contained HTML page. Category and
+/// section ids are used as anchors, e.g. `cheatsheet.html#tuple`.
+#[cfg(feature = "html-export")]
+pub fn html() -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Rust cheatsheet</title>\n<style>\n\
+         body { font-family: sans-serif; max-width: 60em; margin: auto; }\n\
+         pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }\n\
+         code { color: #555; }\n\
+         .timeline { border-collapse: collapse; font-size: 0.9em; }\n\
+         .timeline td, .timeline th { border: 1px solid #ddd; padding: 0.2em 0.5em; text-align: center; }\n\
+         .timeline td:nth-child(2) { text-align: left; }\n\
+         .timeline .own { background: #cde8c4; }\n\
+         .timeline .moved { background: #f6e3a8; }\n\
+         .timeline .borrowed { background: #c6dcf2; }\n\
+         .timeline .dropped { background: #f2c6c6; }\n\
+         </style>\n</head>\n<body>\n<h1>Rust cheatsheet</h1>\n<nav>\n<ul>\n",
+    );
+    for category in Cheatsheet::categories() {
+        writeln!(html, "<li><a href=\"#{}\">{}</a></li>", category.id(), escape(category.title())).unwrap();
+    }
+    html.push_str("</ul>\n</nav>\n");
+    for category in Cheatsheet::categories() {
+        writeln!(html, "<h2 id=\"{}\">{}</h2>", category.id(), escape(category.title())).unwrap();
+        html_body(&mut html, category.markdown());
+        for section in category.sections() {
+            writeln!(html, "<h3 id=\"{}\">{}</h3>", section.id(), escape(section.title())).unwrap();
+            writeln!(html, "<p><code>cheatsheet run {}</code></p>", section.id()).unwrap();
+            html_body(&mut html, section.markdown());
+            let output = section.output();
+            if !output.is_empty() {
+                writeln!(html, "<pre class=\"output\">{}</pre>", escape(&output)).unwrap();
+            }
+            if let Some(events) = section.annotate() {
+                html.push_str(&trace::timeline_html(&events));
+            }
+            let links: Vec<String> = see_also(section)
+                .map(|other| format!("<a href=\"#{}\">{}</a>", other.id(), escape(other.title())))
+                .collect();
+            if !links.is_empty() {
+                writeln!(html, "<p>See also: {}</p>", links.join(", ")).unwrap();
+            }
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// Just enough Markdown for the content files: fenced code blocks become
+// <pre>, tables are kept preformatted, everything else is a paragraph
+#[cfg(feature = "html-export")]
+fn html_body(html: &mut String, body: &str) {
+    let mut lines = body.lines().peekable();
+    while let Some(line) = lines.next() {
+        if let Some(info) = line.strip_prefix("```") {
+            let class = if info.starts_with("rust") { "code" } else { "text" };
+            let code: Vec<&str> = lines.by_ref().take_while(|l| !l.starts_with("```")).collect();
+            if info == "rust,annotated" {
+                continue;
+            }
+            writeln!(html, "<pre class=\"{class}\">{}</pre>", escape(&code.join("\n"))).unwrap();
+        } else if !line.trim().is_empty() {
+            let mut block = vec![line];
+            while let Some(next) = lines.next_if(|l| !l.trim().is_empty() && !l.starts_with("```")) {
+                block.push(next);
+            }
+            let tag = if line.starts_with('|') { "pre" } else { "p" };
+            writeln!(html, "<{tag}>{}</{tag}>", escape(&block.join("\n"))).unwrap();
+        }
+    }
+}
+
+#[cfg(feature = "html-export")]
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// GitHub's heading anchors: lowercase, spaces to '-', punctuation dropped
+fn anchor(title: &str) -> String {
+    title
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchors_match_github() {
+        assert_eq!(anchor("Basic Types & Variables"), "basic-types--variables");
+        assert_eq!(anchor("Packages, Crates, Modules"), "packages-crates-modules");
+    }
+
+    #[test]
+    fn markdown_lists_every_section() {
+        let md = markdown();
+        for section in Cheatsheet::sections() {
+            assert!(md.contains(&format!("`cheatsheet run {}`", section.id())));
+        }
+        assert!(md.contains("```text\nloop count is: 5\n```"));
+        assert!(!md.contains("```rust,module"));
+        assert!(md.contains("`cheatsheet annotate copy-move-clone`"));
+        assert!(!md.contains("```rust,annotated"));
+        assert!(!md.contains("```rust,desugared"));
+        assert!(md.contains("```rust\n// `for pattern in expr { body }` calls IntoIterator::into_iter"));
+    }
+
+    #[test]
+    fn markdown_is_the_same_on_every_run() {
+        // No addresses or timings, regenerating the docs doesn't churn
+        assert_eq!(markdown(), markdown());
+    }
+
+    #[cfg(feature = "html-export")]
+    #[test]
+    fn html_escapes_titles_and_output() {
+        let html = html();
+        assert!(html.contains("<h3 id=\"box\">Box&lt;T&gt; - for allocating values on the heap</h3>"));
+        assert!(html.contains("<pre class=\"output\">loop count is: 5\n</pre>"));
+        assert!(html.contains("<pre class=\"code\">let mut count = 0;"));
+        assert!(html.contains("<td class=\"borrowed\">&amp;</td>"));
+        assert!(!html.contains("Traced::new"));
+        assert_eq!(escape("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
@@ -0,0 +1,171 @@
+use crate::out;
+use crate::trace::{self, Event};
+
+/// A single runnable example, e.g. "Tuple" or "Iterator adaptors",
+/// authored in `content/<category>/<section>.md`.
+#[derive(Debug)]
+pub struct Section {
+    pub(crate) id: &'static str,
+    pub(crate) title: &'static str,
+    pub(crate) tags: &'static [&'static str],
+    pub(crate) see_also: &'static [&'static str],
+    pub(crate) expected_output: Option<&'static str>,
+    pub(crate) no_output: bool,
+    pub(crate) markdown: &'static str,
+    pub(crate) code: &'static str,
+    pub(crate) module_code: &'static str,
+    pub(crate) run: fn(),
+    pub(crate) annotated: Option<fn()>,
+    pub(crate) desugared_code: &'static str,
+    pub(crate) desugared: Option<fn()>,
+}
+
+impl Section {
+    /// Unique, kebab-case identifier used on the command line.
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn title(&self) -> &'static str {
+        self.title
+    }
+
+    pub fn tags(&self) -> &'static [&'static str] {
+        self.tags
+    }
+
+    /// Ids of related sections.
+    pub fn see_also(&self) -> &'static [&'static str] {
+        self.see_also
+    }
+
+    /// What the example prints, `None` for examples that print nothing.
+    pub fn expected_output(&self) -> Option<&'static str> {
+        self.expected_output
+    }
+
+    /// Whether the content file is marked `no-output: true`, i.e. the
+    /// example prints nothing on purpose.
+    pub fn no_output(&self) -> bool {
+        self.no_output
+    }
+
+    /// The Markdown source after the front matter, prose and code blocks.
+    pub fn markdown(&self) -> &'static str {
+        self.markdown
+    }
+
+    /// The code of the ```rust blocks, which is what `run` executes.
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// The code of the ```rust,module blocks: types, impls and tests shared
+    /// with the rest of the category.
+    pub fn module_code(&self) -> &'static str {
+        self.module_code
+    }
+
+    /// Runs the example, printing its output to stdout.
+    pub fn run(&self) {
+        (self.run)()
+    }
+
+    /// Runs the example and returns what it printed instead.
+    pub fn output(&self) -> String {
+        out::capture(self.run)
+    }
+
+    /// Whether [`Section::annotate`] returns something, without running it.
+    pub fn has_annotated(&self) -> bool {
+        self.annotated.is_some()
+    }
+
+    /// Runs the instrumented version of the example, if it has one, and
+    /// returns what its [`Traced`](crate::trace::Traced) values did.
+    pub fn annotate(&self) -> Option<Vec<Event>> {
+        self.annotated.map(trace::record)
+    }
+
+    /// The code of the ```rust,desugared blocks: the example with `?`, `for`
+    /// and friends written out by hand. Empty when there are none.
+    pub fn desugared_code(&self) -> &'static str {
+        self.desugared_code
+    }
+
+    /// Whether [`Section::desugar`] returns something, without running it.
+    pub fn has_desugared(&self) -> bool {
+        self.desugared.is_some()
+    }
+
+    /// Runs the desugared version of the example, if it has one, and returns
+    /// what it printed. It should be the same as [`Section::output`].
+    pub fn desugar(&self) -> Option<String> {
+        self.desugared.map(out::capture)
+    }
+}
+
+/// A group of sections under one banner, e.g. "Collections".
+#[derive(Debug)]
+pub struct Category {
+    pub(crate) id: &'static str,
+    pub(crate) title: &'static str,
+    pub(crate) markdown: &'static str,
+    pub(crate) module_code: &'static str,
+    pub(crate) sections: &'static [Section],
+}
+
+impl Category {
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn title(&self) -> &'static str {
+        self.title
+    }
+
+    /// Introduction from `_category.md`, may be empty.
+    pub fn markdown(&self) -> &'static str {
+        self.markdown
+    }
+
+    /// The ```rust,module blocks of `_category.md`.
+    pub fn module_code(&self) -> &'static str {
+        self.module_code
+    }
+
+    pub fn sections(&self) -> &'static [Section] {
+        self.sections
+    }
+
+    /// Runs every section of the category in order.
+    pub fn run(&self) {
+        for section in self.sections {
+            section.run();
+        }
+    }
+}
+
+/// A category that exists in the cheatsheet but was compiled out of this
+/// build because its cargo feature is disabled.
+#[derive(Debug)]
+pub struct DisabledCategory {
+    pub(crate) id: &'static str,
+    pub(crate) title: &'static str,
+    pub(crate) feature: &'static str,
+}
+
+impl DisabledCategory {
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn title(&self) -> &'static str {
+        self.title
+    }
+
+    /// The cargo feature that compiles the category in.
+    pub fn feature(&self) -> &'static str {
+        self.feature
+    }
+}
@@ -0,0 +1,2 @@
+// One module per category, generated by build.rs from content/
+include!(concat!(env!("OUT_DIR"), "/sections.rs"));
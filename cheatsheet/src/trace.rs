@@ -0,0 +1,418 @@
+//! Instrumented values for the annotated mode of the ownership sections
+//! (`cheatsheet annotate <id>`). The smart pointer sections add stack and
+//! heap diagrams to the same timeline, see [`crate::layout`].
+//!
+//! Moves are plain memcpys the program can't observe, so an example spells
+//! them out with [`Traced::move_to`]. Drops are logged by `Drop` itself, in
+//! the order Rust actually runs them.
+//!
+//! ```
+//! use cheatsheet::trace::{self, Traced};
+//!
+//! let events = trace::record(|| {
+//!     let s1 = Traced::new("s1", String::from("hello"));
+//!     let _s2 = s1.move_to("s2");
+//! });
+//! assert_eq!(events.len(), 3); // s1 owns, moves to s2, s2 drops it
+//! print!("{}", trace::timeline(&events));
+//! ```
+use std::cell::RefCell;
+use std::fmt::{Debug, Write};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// One step of the timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// `owner` is bound to a new value.
+    Own { owner: &'static str, value: String },
+    Move { from: &'static str, to: &'static str, value: String },
+    /// A bitwise copy of a `Copy` value, `from` keeps its own.
+    Copy { from: &'static str, to: &'static str, value: String },
+    /// A deep copy with `Clone`, `from` keeps its own.
+    Clone { from: &'static str, to: &'static str, value: String },
+    Borrow { owner: &'static str, by: &'static str, mutable: bool },
+    BorrowEnd { owner: &'static str, by: &'static str },
+    /// `owner` goes out of scope. `freed` is false for values without drop
+    /// glue, e.g. integers.
+    Drop { owner: &'static str, value: String, freed: bool },
+    /// Free text, e.g. a memory layout diagram.
+    Note(String),
+}
+
+thread_local! {
+    // One log per nested `record` call, like the output capture
+    static EVENTS: RefCell<Vec<Vec<Event>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn log(event: Event) {
+    EVENTS.with(|events| {
+        if let Some(events) = events.borrow_mut().last_mut() {
+            events.push(event);
+        }
+    });
+}
+
+/// Adds a free text step to the timeline.
+pub fn note(text: impl Into<String>) {
+    log(Event::Note(text.into()));
+}
+
+/// Runs `f` and returns everything the traced values did in it. Outside of
+/// `record` nothing is logged.
+pub fn record(f: impl FnOnce()) -> Vec<Event> {
+    struct Pop;
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            EVENTS.with(|events| events.borrow_mut().pop());
+        }
+    }
+
+    EVENTS.with(|events| events.borrow_mut().push(Vec::new()));
+    let _pop = Pop;
+    f();
+    EVENTS.with(|events| mem::take(events.borrow_mut().last_mut().unwrap()))
+}
+
+/// A value that logs what happens to it, owned by the variable `owner`.
+pub struct Traced<T: Debug> {
+    owner: &'static str,
+    // None once moved out, so the old owner doesn't log a drop
+    value: Option<T>,
+}
+
+impl<T: Debug> Traced<T> {
+    pub fn new(owner: &'static str, value: T) -> Self {
+        log(Event::Own { owner, value: format!("{value:?}") });
+        Traced { owner, value: Some(value) }
+    }
+
+    /// `let to = from;` or passing `from` to a parameter named `to`.
+    pub fn move_to(mut self, to: &'static str) -> Self {
+        let value = self.value.take();
+        log(Event::Move { from: self.owner, to, value: format!("{:?}", value.as_ref().unwrap()) });
+        Traced { owner: to, value }
+    }
+
+    /// `let to = from.clone();`
+    pub fn clone_to(&self, to: &'static str) -> Self
+    where
+        T: Clone,
+    {
+        log(Event::Clone { from: self.owner, to, value: format!("{:?}", **self) });
+        Traced { owner: to, value: self.value.clone() }
+    }
+
+    /// `let to = from;` for a `Copy` type.
+    pub fn copy_to(&self, to: &'static str) -> Self
+    where
+        T: Copy,
+    {
+        log(Event::Copy { from: self.owner, to, value: format!("{:?}", **self) });
+        Traced { owner: to, value: self.value }
+    }
+
+    /// `&from`, the borrow ends when the returned guard is dropped.
+    pub fn borrow(&self, by: &'static str) -> Borrowed<'_, T> {
+        log(Event::Borrow { owner: self.owner, by, mutable: false });
+        Borrowed { owner: self.owner, by, value: self }
+    }
+
+    /// `&mut from`, the borrow ends when the returned guard is dropped.
+    pub fn borrow_mut(&mut self, by: &'static str) -> BorrowedMut<'_, T> {
+        log(Event::Borrow { owner: self.owner, by, mutable: true });
+        BorrowedMut { owner: self.owner, by, value: self }
+    }
+}
+
+impl<T: Debug> Deref for Traced<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().expect("value was moved out")
+    }
+}
+
+impl<T: Debug> DerefMut for Traced<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().expect("value was moved out")
+    }
+}
+
+impl<T: Debug> Drop for Traced<T> {
+    fn drop(&mut self) {
+        if let Some(value) = &self.value {
+            let freed = mem::needs_drop::<T>();
+            log(Event::Drop { owner: self.owner, value: format!("{value:?}"), freed });
+        }
+    }
+}
+
+/// A shared borrow of a [`Traced`] value.
+pub struct Borrowed<'a, T: Debug> {
+    owner: &'static str,
+    by: &'static str,
+    value: &'a Traced<T>,
+}
+
+impl<T: Debug> Deref for Borrowed<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: Debug> Drop for Borrowed<'_, T> {
+    fn drop(&mut self) {
+        log(Event::BorrowEnd { owner: self.owner, by: self.by });
+    }
+}
+
+/// A mutable borrow of a [`Traced`] value.
+pub struct BorrowedMut<'a, T: Debug> {
+    owner: &'static str,
+    by: &'static str,
+    value: &'a mut Traced<T>,
+}
+
+impl<T: Debug> Deref for BorrowedMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: Debug> DerefMut for BorrowedMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<T: Debug> Drop for BorrowedMut<'_, T> {
+    fn drop(&mut self) {
+        log(Event::BorrowEnd { owner: self.owner, by: self.by });
+    }
+}
+
+/* ---------------------------------------------------------------------- */
+/* ---------------------------    Timeline    --------------------------- */
+/* ---------------------------------------------------------------------- */
+
+impl Event {
+    /// What happened, in words.
+    pub fn describe(&self) -> String {
+        match self {
+            Event::Own { owner, value } => format!("{owner} owns {value}"),
+            Event::Move { from, to, value } => format!("{value} moves from {from} to {to}"),
+            Event::Copy { from, to, value } => format!("{value} is copied from {from} to {to}"),
+            Event::Clone { from, to, value } => format!("{value} is cloned from {from} to {to}"),
+            Event::Borrow { owner, by, mutable: false } => format!("{by} borrows &{owner}"),
+            Event::Borrow { owner, by, mutable: true } => format!("{by} borrows &mut {owner}"),
+            Event::BorrowEnd { owner, by } => format!("{by} gives back {owner}"),
+            Event::Drop { owner, value, freed: true } => format!("{owner} goes out of scope, {value} is dropped"),
+            Event::Drop { owner, freed: false, .. } => format!("{owner} goes out of scope, nothing to free"),
+            Event::Note(text) => text.clone(),
+        }
+    }
+}
+
+/// The state of one variable after a step, drawn as one cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Empty,
+    /// Got a value in this step.
+    Acquired,
+    /// Holds a value.
+    Owns,
+    MovedOut,
+    Borrowed,
+    Dropped,
+}
+
+impl Cell {
+    fn ascii(self) -> &'static str {
+        match self {
+            Cell::Empty => " ",
+            Cell::Acquired => "*",
+            Cell::Owns => "|",
+            Cell::MovedOut => ">",
+            Cell::Borrowed => "&",
+            Cell::Dropped => "X",
+        }
+    }
+}
+
+/// The variables, in order of appearance, and the state of each of them
+/// after every event. Borrowers are not owners and get no column.
+pub fn grid(events: &[Event]) -> (Vec<&'static str>, Vec<Vec<Cell>>) {
+    let mut owners: Vec<&'static str> = Vec::new();
+    for event in events {
+        let names = match *event {
+            Event::Own { owner, .. } | Event::Drop { owner, .. } => [Some(owner), None],
+            Event::Move { from, to, .. } | Event::Copy { from, to, .. } | Event::Clone { from, to, .. } => {
+                [Some(from), Some(to)]
+            }
+            _ => [None, None],
+        };
+        for name in names.into_iter().flatten() {
+            if !owners.contains(&name) {
+                owners.push(name);
+            }
+        }
+    }
+
+    let column = |name| owners.iter().position(|o| *o == name).unwrap();
+    let mut holding = vec![false; owners.len()];
+    let mut rows = Vec::new();
+    for event in events {
+        let mut row: Vec<Cell> = holding.iter().map(|&h| if h { Cell::Owns } else { Cell::Empty }).collect();
+        let mut set = |name, cell| {
+            row[column(name)] = cell;
+            holding[column(name)] = matches!(cell, Cell::Acquired | Cell::Owns | Cell::Borrowed);
+        };
+        match *event {
+            Event::Own { owner, .. } => set(owner, Cell::Acquired),
+            Event::Move { from, to, .. } => {
+                set(from, Cell::MovedOut);
+                set(to, Cell::Acquired);
+            }
+            Event::Copy { to, .. } | Event::Clone { to, .. } => set(to, Cell::Acquired),
+            Event::Borrow { owner, .. } if owners.contains(&owner) => set(owner, Cell::Borrowed),
+            Event::Drop { owner, .. } => set(owner, Cell::Dropped),
+            _ => {}
+        }
+        rows.push(row);
+    }
+    (owners, rows)
+}
+
+const LEGEND: &str = "* gets a value   | owns it   > moved out   & borrowed   X dropped";
+
+/// Draws the events as an ASCII table, one row per step and one column per
+/// variable.
+pub fn timeline(events: &[Event]) -> String {
+    let (owners, rows) = grid(events);
+    let steps = events.iter().filter(|e| !matches!(e, Event::Note(_)));
+    let width = steps.map(|e| e.describe().chars().count()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    if !owners.is_empty() {
+        write!(out, "step  {:<width$}", "event").unwrap();
+        for owner in &owners {
+            write!(out, "  {owner}").unwrap();
+        }
+        out.push('\n');
+    }
+    let mut step = 0;
+    for (event, row) in events.iter().zip(&rows) {
+        if let Event::Note(text) = event {
+            for line in text.lines() {
+                writeln!(out, "      {line}").unwrap();
+            }
+            continue;
+        }
+        step += 1;
+        write!(out, "{step:>4}  {:<width$}", event.describe()).unwrap();
+        for (owner, cell) in owners.iter().zip(row) {
+            // Centre the mark under the variable name
+            let pad = owner.len() / 2;
+            write!(out, "  {:>pad$}{}{:>rest$}", "", cell.ascii(), "", rest = owner.len() - pad - 1).unwrap();
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+    }
+    if !owners.is_empty() {
+        writeln!(out, "\n{LEGEND}").unwrap();
+    }
+    out
+}
+
+/// Draws the events as an HTML table, with one CSS class per cell state
+/// (`own`, `moved`, `borrowed`, `dropped`).
+#[cfg(feature = "html-export")]
+pub fn timeline_html(events: &[Event]) -> String {
+    let (owners, rows) = grid(events);
+    let mut html = String::from("<table class=\"timeline\">\n<tr><th>step</th><th>event</th>");
+    for owner in &owners {
+        write!(html, "<th>{}</th>", crate::render::escape(owner)).unwrap();
+    }
+    html.push_str("</tr>\n");
+    let mut step = 0;
+    for (event, row) in events.iter().zip(&rows) {
+        if let Event::Note(text) = event {
+            let span = owners.len() + 2;
+            writeln!(html, "<tr><td colspan=\"{span}\"><pre>{}</pre></td></tr>", crate::render::escape(text)).unwrap();
+            continue;
+        }
+        step += 1;
+        write!(html, "<tr><td>{step}</td><td>{}</td>", crate::render::escape(&event.describe())).unwrap();
+        for cell in row {
+            let class = match cell {
+                Cell::Empty => "",
+                Cell::Acquired | Cell::Owns => "own",
+                Cell::MovedOut => "moved",
+                Cell::Borrowed => "borrowed",
+                Cell::Dropped => "dropped",
+            };
+            write!(html, "<td class=\"{class}\">{}</td>", crate::render::escape(cell.ascii().trim())).unwrap();
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_run_in_reverse_declaration_order() {
+        let events = record(|| {
+            let a = Traced::new("a", String::from("a"));
+            let _b = a.clone_to("b");
+            let _x = Traced::new("x", 5).copy_to("y");
+        });
+        let drops: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Drop { owner, .. } => Some(*owner),
+                _ => None,
+            })
+            .collect();
+        // The temporary x is dropped at the end of its statement
+        assert_eq!(drops, ["x", "y", "b", "a"]);
+    }
+
+    #[test]
+    fn moved_values_are_dropped_once() {
+        let events = record(|| {
+            let s1 = Traced::new("s1", String::from("hi"));
+            let s2 = s1.move_to("s2");
+            drop(s2.borrow("len"));
+        });
+        assert_eq!(
+            events,
+            [
+                Event::Own { owner: "s1", value: "\"hi\"".to_owned() },
+                Event::Move { from: "s1", to: "s2", value: "\"hi\"".to_owned() },
+                Event::Borrow { owner: "s2", by: "len", mutable: false },
+                Event::BorrowEnd { owner: "s2", by: "len" },
+                Event::Drop { owner: "s2", value: "\"hi\"".to_owned(), freed: true },
+            ]
+        );
+        let (owners, rows) = grid(&events);
+        assert_eq!(owners, ["s1", "s2"]);
+        assert_eq!(rows[1], [Cell::MovedOut, Cell::Acquired]);
+        assert_eq!(rows[2], [Cell::Empty, Cell::Borrowed]);
+        assert_eq!(rows[4], [Cell::Empty, Cell::Dropped]);
+    }
+
+    #[test]
+    fn nothing_is_logged_outside_record() {
+        let _s = Traced::new("s", 1);
+        assert_eq!(record(|| {}), []);
+    }
+}
@@ -0,0 +1,130 @@
+//! Menu driven browser for the terminal, compiled in with the `tui` feature.
+use crate::{Category, Cheatsheet};
+use std::io::{self, BufRead, Write};
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// Reads menu choices from `input` until `q` or end of input. With
+/// `clear_screen`, each menu is drawn on a cleared terminal.
+pub fn run(mut input: impl BufRead, mut output: impl Write, clear_screen: bool) -> io::Result<()> {
+    let mut ui = Ui { output: &mut output, clear_screen };
+    loop {
+        ui.clear()?;
+        writeln!(ui.output, "Rust cheatsheet\n")?;
+        for (i, category) in Cheatsheet::categories().iter().enumerate() {
+            writeln!(ui.output, "{:>3}) {}", i + 1, category.title())?;
+        }
+        match ui.prompt(&mut input, "category number, q to quit")? {
+            Choice::Number(n) => {
+                if let Some(category) = Cheatsheet::categories().get(n - 1) {
+                    if browse(&mut ui, &mut input, category)? == Choice::Quit {
+                        return Ok(());
+                    }
+                }
+            }
+            Choice::Quit => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+// Section menu of one category, returns Back or Quit
+fn browse(ui: &mut Ui<impl Write>, input: &mut impl BufRead, category: &Category) -> io::Result<Choice> {
+    let mut shown = String::new();
+    loop {
+        ui.clear()?;
+        writeln!(ui.output, "{}\n", category.title())?;
+        for (i, section) in category.sections().iter().enumerate() {
+            writeln!(ui.output, "{:>3}) {}", i + 1, section.title())?;
+        }
+        if !shown.is_empty() {
+            writeln!(ui.output, "\n{shown}")?;
+        }
+        shown.clear();
+        match ui.prompt(input, "section number, a for all, b to go back, q to quit")? {
+            Choice::Number(n) => {
+                if let Some(section) = category.sections().get(n - 1) {
+                    shown = format!("== {} ==\n{}", section.title(), section.output());
+                }
+            }
+            Choice::All => {
+                for section in category.sections() {
+                    shown += &format!("== {} ==\n{}", section.title(), section.output());
+                }
+            }
+            choice @ (Choice::Back | Choice::Quit) => return Ok(choice),
+            Choice::Invalid => {}
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Choice {
+    Number(usize),
+    All,
+    Back,
+    Quit,
+    Invalid,
+}
+
+struct Ui<W> {
+    output: W,
+    clear_screen: bool,
+}
+
+impl<W: Write> Ui<W> {
+    fn clear(&mut self) -> io::Result<()> {
+        if self.clear_screen {
+            write!(self.output, "{CLEAR}")?;
+        }
+        Ok(())
+    }
+
+    fn prompt(&mut self, input: &mut impl BufRead, hint: &str) -> io::Result<Choice> {
+        write!(self.output, "\n{hint}> ")?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(Choice::Quit); // end of input
+        }
+        Ok(match line.trim() {
+            "q" => Choice::Quit,
+            "b" => Choice::Back,
+            "a" => Choice::All,
+            n => match n.parse() {
+                Ok(n) if n > 0 => Choice::Number(n),
+                _ => Choice::Invalid,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(keys: &str) -> String {
+        let mut output = Vec::new();
+        run(keys.as_bytes(), &mut output, false).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn runs_a_section_of_a_category() {
+        let control_flow = Cheatsheet::categories().iter().position(|c| c.id() == "control-flow").unwrap();
+        let output = session(&format!("{}\n2\nq\n", control_flow + 1));
+        assert!(output.contains("== Loop ==\nloop count is: 5\n"));
+    }
+
+    #[test]
+    fn back_returns_to_the_category_menu() {
+        let output = session("1\nb\nq\n");
+        assert_eq!(output.matches("category number, q to quit>").count(), 2);
+    }
+
+    #[test]
+    fn ignores_invalid_choices_and_stops_at_end_of_input() {
+        let output = session("0\nxyz\n999\n");
+        assert_eq!(output.matches("category number, q to quit>").count(), 4);
+    }
+}
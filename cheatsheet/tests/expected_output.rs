@@ -0,0 +1,33 @@
+// Every section prints exactly the `expected-output` of its content file
+use cheatsheet::Cheatsheet;
+
+#[test]
+fn sections_print_their_expected_output() {
+    for section in Cheatsheet::sections() {
+        assert_eq!(
+            section.output(),
+            section.expected_output().unwrap_or(""),
+            "output of `{}` differs from content/*/{}.md",
+            section.id(),
+            section.id()
+        );
+    }
+}
+
+#[test]
+fn desugared_code_prints_the_same() {
+    let desugared: Vec<_> = Cheatsheet::sections().filter_map(|s| Some((s, s.desugar()?))).collect();
+    assert!(!desugared.is_empty());
+    for (section, output) in desugared {
+        assert_eq!(output, section.output(), "desugared code of `{}` prints something else", section.id());
+        assert!(!section.code().contains(section.desugared_code()));
+    }
+}
+
+#[test]
+fn code_is_the_rust_blocks_of_the_markdown() {
+    let section = Cheatsheet::section("loop").unwrap();
+    assert!(section.markdown().contains("```rust\nlet mut count = 0;"));
+    assert!(section.code().starts_with("let mut count = 0;"));
+    assert!(!section.code().contains("```"));
+}
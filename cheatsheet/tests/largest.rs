@@ -0,0 +1,62 @@
+// Integration tests only see the public API, like any other crate would
+use cheatsheet::largest;
+
+// Small xorshift generator, enough to produce varied inputs without extra crates
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn vec(&mut self) -> Vec<i32> {
+        let len = 1 + (self.next() % 32) as usize;
+        (0..len).map(|_| self.next() as i32).collect()
+    }
+}
+
+const CASES: usize = 500;
+
+#[test]
+fn largest_is_an_element_of_the_list() {
+    let mut rng = Rng(0x5EED);
+    for _ in 0..CASES {
+        let list = rng.vec();
+        assert!(list.contains(&largest(&list)), "{list:?}");
+    }
+}
+
+#[test]
+fn largest_is_not_smaller_than_any_element() {
+    let mut rng = Rng(0xC0FFEE);
+    for _ in 0..CASES {
+        let list = rng.vec();
+        let max = largest(&list);
+        assert!(list.iter().all(|&x| x <= max), "{list:?}");
+    }
+}
+
+#[test]
+fn largest_ignores_order() {
+    let mut rng = Rng(0xBEEF);
+    for _ in 0..CASES {
+        let mut list = rng.vec();
+        let max = largest(&list);
+        list.reverse();
+        assert_eq!(largest(&list), max);
+        list.sort_unstable();
+        assert_eq!(largest(&list), max);
+    }
+}
+
+#[test]
+fn largest_matches_iterator_max() {
+    let mut rng = Rng(0x1234_5678);
+    for _ in 0..CASES {
+        let list = rng.vec();
+        assert_eq!(Some(largest(&list)), list.iter().copied().max());
+    }
+}